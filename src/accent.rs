@@ -1,7 +1,11 @@
 use crate::error::{Error, ZbusError};
 
+use futures_lite::StreamExt;
+
 use zbus::zvariant::{OwnedValue, Structure};
 
+const APPEARANCE: &str = "org.freedesktop.appearance";
+
 #[zbus::proxy(
     default_service = "org.freedesktop.portal.Desktop",
     default_path = "/org/freedesktop/portal/desktop",
@@ -10,37 +14,59 @@ use zbus::zvariant::{OwnedValue, Structure};
 )]
 pub trait Settings {
     fn read(&self, namespace: &str, key: &str) -> zbus::Result<OwnedValue>;
+
+    #[zbus(signal)]
+    fn setting_changed(&self, namespace: &str, key: &str, value: OwnedValue) -> zbus::Result<()>;
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Scheme {
     Default,
     Dark,
     Light,
 }
 
+impl Scheme {
+    /// Class of the window, none when there's no preference.
+    pub fn class(self) -> Option<&'static str> {
+        match self {
+            Scheme::Default => None,
+            Scheme::Dark    => Some("dark"),
+            Scheme::Light   => Some("light"),
+        }
+    }
+
+    fn parse(v: &OwnedValue) -> Result<Scheme, Error> {
+        let v = v.downcast_ref::<u32>()
+            .map_err(|_| ZbusError::BadResult { v: format!("{v:?}") })?;
+
+        match v {
+            1 => Ok(Scheme::Dark),
+            2 => Ok(Scheme::Light),
+            _ => Ok(Scheme::Default),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Change {
+    Scheme(Scheme),
+    Accent,
+}
+
 impl Settings<'_> {
     async fn appearance(&self, key: &str) -> Result<OwnedValue, Error> {
-        self.read("org.freedesktop.appearance", key)
+        self.read(APPEARANCE, key)
             .await
             .map_err(|e| ZbusError::Read { e,
-                namespace: "org.freedesktop.appearance".to_string(),
+                namespace: APPEARANCE.to_string(),
                 key: key.to_string()
             })
             .map_err(Into::into)
     }
 
-    #[allow(dead_code)]
     pub async fn scheme(&self) -> Result<Scheme, Error> {
-        let reply = self.appearance("color-scheme").await?;
-
-        let v = reply.downcast_ref::<u32>()
-            .map_err(|_| ZbusError::BadResult { v: format!("{reply:?}") })?;
-
-        match v {
-            1 => Ok(Scheme::Dark),
-            2 => Ok(Scheme::Light),
-            _ => Ok(Scheme::Default),
-        }
+        Scheme::parse(&self.appearance("color-scheme").await?)
     }
 
     pub async fn accent(&self) -> Result<(u8, u8, u8), Error> {
@@ -57,3 +83,41 @@ impl Settings<'_> {
         Ok((r, g, b))
     }
 }
+
+/// Calls `f` with the current color scheme, then with every change of the color scheme
+/// or the accent color, until it returns `false`.
+pub async fn watch(f: impl Fn(Change) -> bool) -> Result<(), Error> {
+    let conn = zbus::Connection::session().await
+        .map_err(|e| ZbusError::Connect { e })?;
+
+    let settings = Settings::new(&conn).await
+        .map_err(|e| ZbusError::Proxy { e })?;
+
+    let mut changes = settings.receive_setting_changed_with_args(&[(0, APPEARANCE)]).await
+        .map_err(|e| ZbusError::Subscribe { e })?;
+
+    if !f(Change::Scheme(settings.scheme().await?)) {
+        return Ok(())
+    }
+
+    while let Some(signal) = changes.next().await {
+        let Ok(args) = signal.args() else { continue };
+
+        let change = match *args.key() {
+            // An unexpected value is reported with it, but doesn't end the subscription
+            "color-scheme" => match Scheme::parse(args.value()) {
+                Ok(scheme) => Change::Scheme(scheme),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    continue
+                },
+            },
+            "accent-color" => Change::Accent,
+            _ => continue,
+        };
+
+        if !f(change) { break }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use gtk::prelude::{BoxExt, OrientableExt, WidgetExt};
+use gtk::{Align, Orientation};
+
+use relm4::{ComponentParts, ComponentSender, SimpleComponent};
+
+use jiff::civil::Date;
+use jiff::ToSpan;
+
+use crate::cal;
+use crate::event::Event;
+use crate::widgets::eventlabel;
+
+#[tracker::track]
+pub struct Agenda {
+    today: Date,
+
+    #[no_eq]
+    events: Vec<Event>,
+
+    #[do_not_track]
+    days: u16,
+}
+
+pub struct Config {
+    pub today: Date,
+    pub days:  u16,
+}
+
+#[derive(Debug)]
+pub enum AgendaMessage {
+    SetToday(Date),
+    SetEvents(Vec<Event>),
+}
+
+impl Agenda {
+    fn fill(&self, container: &gtk::Box) {
+        while let Some(child) = container.first_child() {
+            container.remove(&child);
+        }
+
+        let mut empty = true;
+
+        for n in 0..self.days as i64 {
+            let date = self.today + n.days();
+
+            let labels = eventlabel::for_day(&self.events, date);
+
+            if labels.is_empty() { continue }
+
+            empty = false;
+
+            let title = gtk::Label::new(Some(&format!("{} · {} {}", relative(n), date.day(), cal::monthname(date.month() as _))));
+            title.add_css_class("title");
+            title.set_halign(Align::Start);
+            container.append(&title);
+
+            for label in labels {
+                container.append(&label);
+            }
+        }
+
+        if empty {
+            let label = gtk::Label::new(Some("Nothing planned"));
+            label.add_css_class("empty");
+            container.append(&label);
+        }
+    }
+}
+
+fn relative(days: i64) -> String {
+    match days {
+        0 => String::from("Today"),
+        1 => String::from("Tomorrow"),
+        n => format!("In {n} days"),
+    }
+}
+
+#[relm4::component(pub)]
+impl SimpleComponent for Agenda {
+    type Init = Config;
+    type Input = AgendaMessage;
+    type Output = ();
+
+    view! {
+        gtk::ScrolledWindow {
+            add_css_class: "agenda",
+            set_hscrollbar_policy: gtk::PolicyType::Never,
+            set_min_content_height: 220,
+
+            #[name = "list"]
+            gtk::Box {
+                set_orientation: Orientation::Vertical,
+            },
+        }
+    }
+
+    fn post_view() {
+        if self.changed(Self::today() | Self::events()) {
+            self.fill(list);
+        }
+    }
+
+    fn init(config: Self::Init, root: Self::Root, _sender: ComponentSender<Self>) -> ComponentParts<Self> {
+        let model = Agenda {
+            today:  config.today,
+            events: Vec::new(),
+            days:   config.days,
+
+            tracker: 0,
+        };
+
+        let widgets = view_output!();
+
+        model.fill(&widgets.list);
+
+        ComponentParts { model, widgets }
+    }
+
+    fn update(&mut self, message: Self::Input, _: ComponentSender<Self>) {
+        self.reset();
+
+        match message {
+            AgendaMessage::SetToday(date) => self.set_today(date),
+            AgendaMessage::SetEvents(events) => self.set_events(events),
+        }
+    }
+}
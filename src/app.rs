@@ -1,13 +1,15 @@
 use std::borrow::Cow;
+use std::path::PathBuf;
 
 use gtk::gdk::ScrollUnit;
 use gtk::graphene::Point;
+use gtk::gio;
 use gtk::{Align, EventControllerScrollFlags, IconTheme, Orientation, Window};
-use gtk::prelude::{ApplicationExt, GtkWindowExt, OrientableExt, WidgetExt};
+use gtk::prelude::{ActionGroupExt, ActionMapExt, ApplicationCommandLineExt, ApplicationExt, BoxExt, CastNone, EventControllerExt, FileExt, FileMonitorExt, GestureSingleExt, GtkWindowExt, OrientableExt, ToVariant, WidgetExt};
 
 use relm4::component::{AsyncComponent, AsyncComponentSender, AsyncComponentParts};
 use relm4::once_cell::sync::OnceCell;
-use relm4::RelmWidgetExt;
+use relm4::{Component, ComponentController, Controller, RelmWidgetExt};
 
 use jiff::civil::Date;
 use jiff::{ToSpan, Zoned};
@@ -15,11 +17,19 @@ use jiff::{ToSpan, Zoned};
 use smallvec::SmallVec;
 
 use crate::event::Event;
-use crate::{cal, event};
+use crate::holiday::Holiday;
+use crate::note::Note;
+use crate::{cal, calendar, clock, holiday, ics, note};
+use crate::agenda::{self, Agenda, AgendaMessage};
 use crate::anchor::Anchor;
+use crate::error::CLIError;
+use crate::keymap::{Action, Keymap};
 use crate::style::{self, StyleSettings};
 use crate::widgets::anilabel::AniLabel;
+use crate::widgets::eventlabel;
 use crate::widgets::monthgrid::MonthGrid;
+use crate::widgets::weekgrid::WeekGrid;
+use crate::widgets::yeargrid::YearGrid;
 
 pub static WM_CONFIG: OnceCell<WMConfig> = const { OnceCell::new() };
 
@@ -28,16 +38,387 @@ pub struct App {
     date:  Date,
     today: Date,
     drag:  f32,
+    view:  View,
 
     #[no_eq]
-    events: SmallVec<[Event; 10]>
+    events: SmallVec<[Event; 10]>,
+
+    #[do_not_track]
+    sources: Vec<PathBuf>,
+
+    #[do_not_track]
+    _monitors: Vec<gio::FileMonitor>,
+
+    #[do_not_track]
+    userstyle: Option<PathBuf>,
+
+    #[do_not_track]
+    style_settings: StyleSettings,
+
+    #[do_not_track]
+    _style_monitors: Vec<gio::FileMonitor>,
+
+    #[do_not_track]
+    agenda: Controller<Agenda>,
+
+    #[do_not_track]
+    calendar: Option<&'static dyn calendar::System>,
+
+    #[no_eq]
+    notes: Vec<Date>,
+
+    #[no_eq]
+    holidays: Vec<Holiday>,
+
+    selected: Option<Date>,
+    focused:  Option<Date>,
+
+    #[no_eq]
+    selected_notes: Vec<Note>,
 }
 
 impl App {
-    // TODO: load user events
-    // async fn refresh_events(&mut self) -> Result<(), std::io::Error> {
-        // Ok(())
-    // }
+    fn refresh_events(&self, sender: &AsyncComponentSender<Self>) {
+        sender.oneshot_command(Self::read_events(self.sources.clone()));
+    }
+
+    async fn read_events(sources: Vec<PathBuf>) -> CommandMessage {
+        let mut events = Vec::new();
+
+        for path in sources {
+            match ics::read(&path).await {
+                Ok(e) => events.extend(e),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+
+        CommandMessage::SetEvents(events)
+    }
+
+    // Calls `f` once one of the files is written, replaced or removed
+    fn watch(paths: &[PathBuf], f: impl Fn() + Clone + 'static) -> Vec<gio::FileMonitor> {
+        paths.iter()
+            .filter_map(|path| {
+                let monitor = gio::File::for_path(path)
+                    .monitor_file(gio::FileMonitorFlags::WATCH_MOVES, gio::Cancellable::NONE);
+
+                match monitor {
+                    Ok(monitor) => Some(monitor),
+                    Err(e) => {
+                        eprintln!("Unable to watch a file for changes ({})\n{e}", path.display());
+                        None
+                    }
+                }
+            })
+            .inspect(|monitor| {
+                monitor.connect_changed({
+                    let f = f.clone();
+
+                    move |_, _, _, event| {
+                        use gio::FileMonitorEvent::*;
+
+                        if matches!(event, ChangesDoneHint | Created | Deleted | MovedIn | MovedOut | Renamed) {
+                            f();
+                        }
+                    }
+                });
+            })
+            .collect()
+    }
+
+    // Re-reads every source once one of them changes
+    fn watch_events(sources: &[PathBuf], sender: &AsyncComponentSender<Self>) -> Vec<gio::FileMonitor> {
+        let (paths, sender) = (sources.to_vec(), sender.clone());
+
+        Self::watch(sources, move || sender.oneshot_command(Self::read_events(paths.clone())))
+    }
+
+    // Reads the userstyle, or the style in the config directory, and reports the files it's made of
+    // for watching. Only the first load falls back to the default style, a broken edit keeps the last one.
+    fn load_style(userstyle: Option<PathBuf>, settings: StyleSettings, fallback: bool, sender: &AsyncComponentSender<Self>) {
+        sender.command(move |out, shutdown| shutdown.register(async move {
+            let (style, path) = match userstyle {
+                Some(p) => (style::read(&p).await, p),
+                None    => {
+                    let config_dir = crate::config_dir().await.unwrap();
+                    (style::find(&config_dir, settings.clone()).await, style::locate(&config_dir))
+                },
+            };
+
+            let style = match style {
+                Ok(s) => Some(s),
+                Err(e) => {
+                    eprintln!("{}", e);
+
+                    match fallback {
+                        true  => Some(style::default(settings.clone()).await),
+                        false => None,
+                    }
+                }
+            };
+
+            if let Some(style) = style {
+                out.emit(CommandMessage::SetStyle(style::set_vars(style, &settings.vars)));
+            }
+
+            out.emit(CommandMessage::WatchStyle(style::dependencies(path).await));
+        }).drop_on_shutdown());
+    }
+
+    fn default_sources() -> Vec<PathBuf> {
+        let mut path = crate::xdg::config_dir();
+        path.push(crate::APP_BINARY);
+        path.push("events.ics");
+
+        match path.exists() {
+            true  => vec![path],
+            false => Vec::new(),
+        }
+    }
+
+    fn load_holidays(regions: Vec<String>, sender: &AsyncComponentSender<Self>) {
+        sender.oneshot_command(async move {
+            let mut holidays = Vec::new();
+
+            for region in regions {
+                match holiday::read(&region).await {
+                    Ok(h) => holidays.extend(h),
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+
+            CommandMessage::SetHolidays(holidays)
+        });
+    }
+
+    fn refresh_notes(&self, sender: &AsyncComponentSender<Self>) {
+        // Wide enough to cover days of adjacent months shown in the grid
+        let from = self.date.first_of_month() - 1.week();
+        let to = self.date.last_of_month().saturating_add(2.weeks());
+
+        sender.oneshot_command(async move {
+            match note::dates(from, to).await {
+                Ok(dates) => CommandMessage::SetNotes(dates),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    CommandMessage::SetNotes(Vec::new())
+                }
+            }
+        });
+    }
+
+    fn refresh_selected_notes(&self, sender: &AsyncComponentSender<Self>) {
+        let Some(date) = self.selected else { return };
+
+        sender.oneshot_command(async move {
+            match note::read(date).await {
+                Ok(notes) => CommandMessage::SetSelectedNotes(date, notes),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    CommandMessage::SetSelectedNotes(date, Vec::new())
+                }
+            }
+        });
+    }
+
+    fn select(&mut self, date: Date, sender: &AsyncComponentSender<Self>) {
+        match self.selected == Some(date) {
+            true  => self.set_selected(None),
+            false => self.set_selected(Some(date)),
+        }
+
+        self.set_selected_notes(Vec::new());
+        self.refresh_selected_notes(sender);
+    }
+
+    // Months of the secondary calendar that the shown month overlaps
+    fn secondary_monthname(&self) -> String {
+        self.calendar
+            .and_then(|system| calendar::monthnames(system, self.date.first_of_month(), self.date.last_of_month()))
+            .unwrap_or_default()
+    }
+
+    // Day that keyboard navigation and the week view start from
+    fn anchor(&self) -> Date {
+        self.focused
+            .or(self.selected)
+            .unwrap_or(match self.today.first_of_month() == self.date.first_of_month() {
+                true  => self.today,
+                false => self.date,
+            })
+    }
+
+    fn key(&mut self, action: Action, sender: &AsyncComponentSender<Self>) {
+        let focused = self.anchor();
+
+        let focus = match action {
+            Action::Left      => focused.checked_sub(1.day()),
+            Action::Right     => focused.checked_add(1.day()),
+            Action::Up        => focused.checked_sub(1.week()),
+            Action::Down      => focused.checked_add(1.week()),
+            // Pages like the header buttons, by the step of the view
+            Action::PrevMonth => return sender.input(ElementMessage::PrevMonth),
+            Action::NextMonth => return sender.input(ElementMessage::NextMonth),
+            Action::PrevYear  => focused.checked_sub(1.year()),
+            Action::NextYear  => focused.checked_add(1.year()),
+            Action::Today     => Ok(self.today),
+            Action::Select    => return self.select(focused, sender),
+            Action::MonthView => return sender.input(ElementMessage::SetView(View::Month)),
+            Action::WeekView  => return sender.input(ElementMessage::SetView(View::Week)),
+            Action::YearView  => return sender.input(ElementMessage::SetView(View::Year)),
+            Action::AgendaView => return sender.input(ElementMessage::SetView(View::Agenda)),
+            Action::Quit      => return relm4::main_application().quit(),
+        };
+
+        let Some(focus) = focus.ok().filter(|date| cal::is_supported(date.year())) else { return };
+
+        self.set_focused(Some(focus));
+
+        // The week view follows the focus from week to week
+        if self.view == View::Week {
+            self.set_date(focus);
+            self.set_drag(0.0);
+        }
+        else if focus.first_of_month() != self.date.first_of_month() {
+            self.set_date(focus.first_of_month());
+            self.set_drag(0.0);
+        }
+    }
+
+    fn fill_details(&self, container: &gtk::Box) {
+        while let Some(child) = container.first_child() {
+            container.remove(&child);
+        }
+
+        let Some(date) = self.selected else { return };
+
+        let title = gtk::Label::new(Some(&format!("{} {}", date.day(), cal::monthname(date.month() as _))));
+        title.add_css_class("title");
+        title.set_halign(Align::Start);
+        container.append(&title);
+
+        for label in eventlabel::for_day(&self.events, date) {
+            container.append(&label);
+        }
+
+        for note in &self.selected_notes {
+            let text = match note.time {
+                Some(time) => format!("{} {}", time.strftime("%H:%M"), note.text),
+                None       => note.text.clone(),
+            };
+
+            let label = gtk::Label::new(Some(&text));
+            label.add_css_class("note");
+            label.set_halign(Align::Start);
+            label.set_wrap(true);
+            container.append(&label);
+        }
+    }
+
+    fn translate(&self) -> Point {
+        const MAX_DISTANCE: f32 = 0.68;
+        const REQUIRED_STRENGTH: f32 = 0.34;
+
+        let mut x = (self.drag.abs() - 1.0).powi(2);
+        x = 1.0 + x * (MAX_DISTANCE * x - REQUIRED_STRENGTH);
+
+        Point::new(x.copysign(self.drag), 0.0)
+    }
+
+    fn drag_controller(sender: &AsyncComponentSender<Self>) -> gtk::EventControllerScroll {
+        let controller = gtk::EventControllerScroll::new(EventControllerScrollFlags::HORIZONTAL | EventControllerScrollFlags::KINETIC);
+
+        controller.connect_scroll({
+            let sender = sender.clone();
+            move |e, x, _| {
+                if e.unit() == ScrollUnit::Wheel { return glib::Propagation::Proceed }
+
+                sender.input(ElementMessage::Drag(x));
+                glib::Propagation::Stop
+            }
+        });
+
+        controller.connect_decelerate({
+            let sender = sender.clone();
+            move |e, x, _| {
+                if e.unit() == ScrollUnit::Wheel { return }
+
+                const PULL_STRENGTH: f64 = 400.0;
+
+                let message = match x {
+                    x if x >  PULL_STRENGTH => ElementMessage::PrevMonth,
+                    x if x < -PULL_STRENGTH => ElementMessage::NextMonth,
+                    _ => ElementMessage::Drag(0.0),
+                };
+
+                sender.input(message);
+            }
+        });
+
+        controller
+    }
+
+    /// Handles `show`, `hide` or `toggle`, optionally followed by `goto <date>` and `reload`, sent by
+    /// every launch to the first instance, including itself.
+    pub fn command_line(application: &gtk::Application, command_line: &gio::ApplicationCommandLine) -> i32 {
+        let args: Vec<String> = command_line.arguments().iter()
+            .skip(1)
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+
+        let mut args = args.iter().map(String::as_str);
+
+        match args.next() {
+            Some("show")   => application.activate(),
+            Some("hide")   => application.activate_action("hide", None),
+            Some("toggle") => application.activate_action("toggle", None),
+            _ => return 1,
+        }
+
+        while let Some(arg) = args.next() {
+            match arg {
+                "goto" => {
+                    let Some(date) = args.next() else { return 1 };
+                    application.activate_action("goto", Some(&date.to_variant()));
+                },
+                "reload" => application.activate_action("reload", None),
+                _ => return 1,
+            }
+        }
+
+        0
+    }
+
+    /// Targets of `App::command_line`, which can also be called with `gapplication action elvy.caffi <name>`.
+    /// Registered before the app runs, since the first instance handles its own command line before `init`;
+    /// messages sent until then wait in the channel that `init` forwards to the app.
+    pub fn add_actions(application: &gtk::Application, sender: &relm4::Sender<ElementMessage>) {
+        let actions = [
+            ("show",   ElementMessage::Show),
+            ("hide",   ElementMessage::Hide),
+            ("toggle", ElementMessage::Toggle),
+            ("reload", ElementMessage::Reload),
+        ];
+
+        for (name, message) in actions {
+            let action = gio::SimpleAction::new(name, None);
+            let sender = sender.clone();
+
+            action.connect_activate(move |_, _| sender.emit(message.clone()));
+            application.add_action(&action);
+        }
+
+        let action = gio::SimpleAction::new("goto", Some(glib::VariantTy::STRING));
+        let sender = sender.clone();
+
+        action.connect_activate(move |_, parameter| {
+            if let Some(date) = parameter.and_then(|p| p.str()).and_then(cal::parse_date) {
+                sender.emit(ElementMessage::GoTo(date));
+            }
+        });
+
+        application.add_action(&action);
+    }
 
     fn load_icons(window: &Window) {
         gtk::gio::resources_register_include!("icons.gresource").unwrap();
@@ -48,27 +429,100 @@ impl App {
 
 pub struct Config {
     pub first: String,
-    pub userstyle: Option<std::path::PathBuf>,
+    pub weekend: u8,
+    pub events: Vec<PathBuf>,
+    pub keymap: Keymap,
+    pub week_numbers: bool,
+    pub calendar: Option<&'static dyn calendar::System>,
+    pub holidays: Vec<String>,
+    pub view: View,
+    pub agenda_days: u16,
+    pub userstyle: Option<PathBuf>,
+    pub vars: Vec<(String, String)>,
+    pub actions: relm4::Receiver<ElementMessage>,
 
     #[cfg(feature = "Accent")]
     pub accent: bool,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum View {
+    Month,
+    Week,
+    Year,
+    Agenda,
+}
+
+impl View {
+    fn name(&self) -> &'static str {
+        match self {
+            View::Month => "month",
+            View::Week  => "week",
+            View::Year  => "year",
+            View::Agenda => "agenda",
+        }
+    }
+
+    // Distance covered by the previous and next buttons
+    fn step(&self) -> jiff::Span {
+        match self {
+            View::Month => 1.month(),
+            View::Week  => 1.week(),
+            View::Year  => 1.year(),
+            View::Agenda => 1.month(),
+        }
+    }
+}
+
+impl TryFrom<&str> for View {
+    type Error = CLIError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "month" => Ok(View::Month),
+            "week"  => Ok(View::Week),
+            "year"  => Ok(View::Year),
+            "agenda" => Ok(View::Agenda),
+            _       => Err(CLIError::View(s.to_owned())),
+        }
+    }
+}
+
 pub struct WMConfig {
     pub anchors: Anchor,
     pub margins: Vec<i32>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ElementMessage {
     PrevMonth,
     NextMonth,
     Drag(f64),
+    Select(Date),
+    Key(Action),
+    ToggleYear,
+    ShowMonth(u8),
+    SetView(View),
+    Show,
+    Hide,
+    Toggle,
+    GoTo(Date),
+    Reload,
 }
 
 #[derive(Debug)]
 pub enum CommandMessage {
     SetStyle(Cow<'static, str>),
+    WatchStyle(Vec<PathBuf>),
+    SetEvents(Vec<Event>),
+    SetToday(Date),
+    SetNotes(Vec<Date>),
+    SetHolidays(Vec<Holiday>),
+    SetSelectedNotes(Date, Vec<Note>),
+
+    #[cfg(feature = "Accent")]
+    Appearance(crate::accent::Change),
+
     Quit,
 }
 
@@ -85,6 +539,18 @@ impl AsyncComponent for App {
             set_title:     Some(crate::APP_NAME),
             set_decorated: false,
 
+            add_controller = gtk::EventControllerKey {
+                connect_key_pressed[sender, keymap = config.keymap] => move |_, key, _, modifiers| {
+                    match keymap.action(key, modifiers) {
+                        Some(action) => {
+                            sender.input(ElementMessage::Key(action));
+                            glib::Propagation::Stop
+                        },
+                        None => glib::Propagation::Proceed,
+                    }
+                }
+            },
+
             gtk::Box {
                 add_css_class: "calendar",
                 set_orientation: Orientation::Vertical,
@@ -96,6 +562,17 @@ impl AsyncComponent for App {
                             add_css_class: "month",
                             #[track = "self.changed(Self::date())"]
                             set_animated_text: cal::monthname(model.date.month() as _),
+                            #[track = "self.changed(Self::view())"]
+                            set_visible: model.view == View::Month,
+                        },
+
+                        gtk::Label {
+                            add_css_class: "altmonth",
+                            set_halign: Align::Start,
+                            #[track = "self.changed(Self::date())"]
+                            set_label: &model.secondary_monthname(),
+                            #[track = "self.changed(Self::view())"]
+                            set_visible: model.view == View::Month && model.calendar.is_some(),
                         },
 
                         gtk::Label {
@@ -103,6 +580,12 @@ impl AsyncComponent for App {
                             #[track = "self.changed(Self::date())"]
                             set_label: &model.date.year().to_string(),
                             set_halign: Align::Start,
+                            add_controller = gtk::GestureClick {
+                                set_button: 1,
+                                connect_pressed[sender] => move |_, _, _, _| {
+                                    sender.input(ElementMessage::ToggleYear);
+                                }
+                            },
                         },
                     },
 
@@ -121,51 +604,120 @@ impl AsyncComponent for App {
                     }
                 },
 
-                #[name = "monthgrid"]
-                MonthGrid {
-                    #[track = "self.changed(Self::drag())"]
-                    set_translate: {
-                        const MAX_DISTANCE: f32 = 0.68;
-                        const REQUIRED_STRENGTH: f32 = 0.34;
+                gtk::Stack {
+                    set_transition_type: gtk::StackTransitionType::Crossfade,
+                    #[track = "self.changed(Self::view())"]
+                    set_visible_child_name: model.view.name(),
 
-                        let mut x = (model.drag.abs() - 1.0).powi(2);
-                        x = 1.0 + x * (MAX_DISTANCE * x - REQUIRED_STRENGTH);
+                    add_named[Some(View::Month.name())] = &gtk::Box {
+                        set_orientation: Orientation::Vertical,
 
-                        Point::new(x.copysign(model.drag), 0.0)
-                    },
-                    set_max_weekday_chars: 2,
-                    set_first: config.first,
-                    #[track = "self.changed(Self::date())"]
-                    set_date: (model.date.year() as _, model.date.month() as _),
-                    add_controller = gtk::EventControllerScroll {
-                        set_flags: EventControllerScrollFlags::HORIZONTAL | EventControllerScrollFlags::KINETIC,
-                        connect_scroll[sender] => move |e, x, _| {
-                            if e.unit() == ScrollUnit::Wheel { return glib::Propagation::Proceed }
-
-                            sender.input(ElementMessage::Drag(x));
-                            glib::Propagation::Stop
+                        #[name = "monthgrid"]
+                        MonthGrid {
+                            #[track = "self.changed(Self::drag())"]
+                            set_translate: model.translate(),
+                            set_max_weekday_chars: 2,
+                            set_first: config.first.clone(),
+                            set_weekend: config.weekend,
+                            set_week_numbers: config.week_numbers,
+                            set_calendar: config.calendar.map(|system| system.name()).unwrap_or_default(),
+                            #[track = "self.changed(Self::date())"]
+                            set_date: (model.date.year() as _, model.date.month() as _),
+                            add_controller: Self::drag_controller(&sender),
+                            #[track = "self.changed(Self::date() | Self::today())"]
+                            set_today: model.today,
+                            #[track = "self.changed(Self::date() | Self::events())"]
+                            set_events: &model.events,
+                            #[track = "self.changed(Self::date() | Self::notes())"]
+                            set_notes: &model.notes,
+                            #[track = "self.changed(Self::date() | Self::holidays())"]
+                            set_holidays: &model.holidays,
+                            #[track = "self.changed(Self::date() | Self::selected())"]
+                            set_selected: model.selected,
+                            #[track = "self.changed(Self::date() | Self::focused())"]
+                            set_focused: model.focused,
+                            add_controller = gtk::GestureClick {
+                                set_button: 1,
+                                connect_pressed[sender] => move |gesture, _, x, y| {
+                                    let Some(grid) = gesture.widget().and_downcast::<MonthGrid>() else { return };
+
+                                    if let Some(date) = grid.date_at(x, y) {
+                                        sender.input(ElementMessage::Select(date));
+                                    }
+                                }
+                            },
                         },
-                        connect_decelerate[sender] => move |e, x, _| {
-                            if e.unit() == ScrollUnit::Wheel { return }
 
-                            const PULL_STRENGTH: f64 = 400.0;
+                        gtk::Revealer {
+                            set_transition_type: gtk::RevealerTransitionType::SlideDown,
+                            #[track = "self.changed(Self::selected())"]
+                            set_reveal_child: model.selected.is_some(),
 
-                            let message = match x {
-                                x if x >  PULL_STRENGTH => ElementMessage::PrevMonth,
-                                x if x < -PULL_STRENGTH => ElementMessage::NextMonth,
-                                _ => ElementMessage::Drag(0.0),
-                            };
+                            #[name = "details"]
+                            gtk::Box {
+                                add_css_class: "details",
+                                set_orientation: Orientation::Vertical,
+                            },
+                        },
+                    },
 
-                            sender.input(message);
-                        }
+                    add_named[Some(View::Week.name())] = &gtk::ScrolledWindow {
+                        set_hscrollbar_policy: gtk::PolicyType::Never,
+                        set_min_content_height: 220,
+
+                        #[wrap(Some)]
+                        set_child = &WeekGrid {
+                            #[track = "self.changed(Self::drag())"]
+                            set_translate: model.translate(),
+                            set_max_weekday_chars: 2,
+                            set_first: config.first.clone(),
+                            #[track = "self.changed(Self::date() | Self::view() | Self::today()) && model.view == View::Week"]
+                            set_date: model.date,
+                            #[track = "self.changed(Self::date() | Self::view() | Self::today() | Self::events()) && model.view == View::Week"]
+                            set_events: &model.events,
+                            #[track = "self.changed(Self::date() | Self::view() | Self::today()) && model.view == View::Week"]
+                            set_today: model.today,
+                            #[track = "self.changed(Self::date() | Self::view() | Self::today() | Self::focused()) && model.view == View::Week"]
+                            set_focused: model.focused,
+                            add_controller: Self::drag_controller(&sender),
+                        },
+                    },
+
+                    add_named: (model.agenda.widget(), Some(View::Agenda.name())),
+
+                    add_named[Some(View::Year.name())] = &YearGrid {
+                        set_first: config.first,
+                        set_weekend: config.weekend,
+                        #[track = "self.changed(Self::date() | Self::view()) && model.view == View::Year"]
+                        set_date: model.date.year() as _,
+                        #[track = "self.changed(Self::date() | Self::view() | Self::today()) && model.view == View::Year"]
+                        set_today: model.today,
+                        #[track = "self.changed(Self::date() | Self::view() | Self::holidays()) && model.view == View::Year"]
+                        set_holidays: &model.holidays,
+                        #[track = "self.changed(Self::date() | Self::view() | Self::events()) && model.view == View::Year"]
+                        set_events: &model.events,
+                        add_controller = gtk::GestureClick {
+                            set_button: 1,
+                            connect_pressed[sender] => move |gesture, _, x, y| {
+                                let Some(grid) = gesture.widget().and_downcast::<YearGrid>() else { return };
+
+                                if let Some(month) = grid.month_at(x, y) {
+                                    sender.input(ElementMessage::ShowMonth(month));
+                                }
+                            }
+                        },
                     },
-                    #[track = "self.changed(Self::date())"]
-                    set_event: event::today(),
                 },
             },
         }
     }
 
+    fn post_view() {
+        if self.changed(Self::selected() | Self::selected_notes() | Self::events()) {
+            self.fill_details(details);
+        }
+    }
+
     fn init_loading_widgets(window: Self::Root) -> Option<relm4::loading_widgets::LoadingWidgets> {
         let config = WM_CONFIG.get().unwrap();
 
@@ -189,31 +741,18 @@ impl AsyncComponent for App {
             glib::log_set_writer_func(|_, _| glib::LogWriterOutput::Handled);
         }
 
-        sender.oneshot_command(async move {
-            #[allow(unused_mut)]
-            let mut settings = StyleSettings::default();
+        let sources = match config.events.is_empty() {
+            true  => Self::default_sources(),
+            false => config.events,
+        };
 
+        let style_settings = StyleSettings {
             #[cfg(feature = "Accent")]
-            { settings.accent = config.accent; }
-
-            let style = match config.userstyle {
-                Some(p) => style::read(p).await,
-                None    => {
-                    let config_dir = crate::config_dir().await.unwrap();
-                    style::find(config_dir, settings).await
-                },
-            };
-
-            let style = match style {
-                Ok(s) => s,
-                Err(e) => {
-                    eprintln!("{}", e);
-                    style::default(settings).await
-                }
-            };
+            accent: config.accent,
+            vars: config.vars,
+        };
 
-            CommandMessage::SetStyle(style)
-        });
+        Self::load_style(config.userstyle.clone(), style_settings.clone(), true, &sender);
 
         sender.oneshot_command(async move {
             use tokio::signal::*;
@@ -224,16 +763,66 @@ impl AsyncComponent for App {
             CommandMessage::Quit
         });
 
+        let agenda = Agenda::builder()
+            .launch(agenda::Config { today: Zoned::now().date(), days: config.agenda_days })
+            .detach();
+
         let model = App {
             date:  Zoned::now().date(),
             today: Zoned::now().date(),
             drag:  0.0,
+            view:  config.view,
 
             events: SmallVec::new_const(),
+            notes:  Vec::new(),
+            holidays: Vec::new(),
+
+            selected: None,
+            focused:  None,
+            selected_notes: Vec::new(),
+
+            _monitors: Self::watch_events(&sources, &sender),
+            sources,
+            userstyle: config.userstyle,
+            style_settings,
+            _style_monitors: Vec::new(),
+            agenda,
+            calendar: config.calendar,
 
             tracker: 0,
         };
 
+        model.refresh_events(&sender);
+        model.refresh_notes(&sender);
+
+        let input = sender.input_sender().clone();
+        let actions = config.actions;
+
+        relm4::spawn_local(async move {
+            while let Some(message) = actions.recv().await {
+                input.emit(message);
+            }
+        });
+
+        #[cfg(feature = "DBus")]
+        if let Err(e) = crate::dbus::serve(sender.input_sender().clone(), model.date).await {
+            eprintln!("{}", e);
+        }
+
+        #[cfg(feature = "Accent")]
+        sender.command(|out, shutdown| shutdown.register(async move {
+            if let Err(e) = crate::accent::watch(move |change| out.send(CommandMessage::Appearance(change)).is_ok()).await {
+                eprintln!("{}", e);
+            }
+        }).drop_on_shutdown());
+
+        let out = sender.command_sender().clone();
+
+        if let Err(e) = clock::on_day_change(model.today, move |date| out.send(CommandMessage::SetToday(date)).is_ok()) {
+            eprintln!("{}", e);
+        }
+        Self::load_holidays(config.holidays, &sender);
+
         let widgets = view_output!();
         let controller = gtk::GestureClick::builder().button(1).build();
         controller.connect_pressed({
@@ -270,7 +859,7 @@ impl AsyncComponent for App {
         AsyncComponentParts { model, widgets }
     }
 
-    async fn update(&mut self, message: Self::Input, _: AsyncComponentSender<Self>, _: &Self::Root) {
+    async fn update(&mut self, message: Self::Input, sender: AsyncComponentSender<Self>, window: &Self::Root) {
         use ElementMessage::*;
         self.reset();
 
@@ -282,19 +871,112 @@ impl AsyncComponent for App {
                 if x == 0.0 { self.set_drag(0.0) }
             },
             PrevMonth => {
-                self.set_date(self.date - 1.month());
+                if let Some(date) = self.date.checked_sub(self.view.step()).ok().filter(|date| cal::is_supported(date.year())) {
+                    self.set_date(date);
+                    self.set_focused(self.focused.and_then(|focused| focused.checked_sub(self.view.step()).ok()));
+                }
+
                 self.set_drag(0.0);
             },
             NextMonth => {
-                self.set_date(self.date + 1.month());
+                if let Some(date) = self.date.checked_add(self.view.step()).ok().filter(|date| cal::is_supported(date.year())) {
+                    self.set_date(date);
+                    self.set_focused(self.focused.and_then(|focused| focused.checked_add(self.view.step()).ok()));
+                }
+
+                self.set_drag(0.0);
+            },
+            ToggleYear => match self.view {
+                View::Year => self.set_view(View::Month),
+                _          => self.set_view(View::Year),
+            },
+            SetView(view) => {
+                if view == View::Week {
+                    self.set_date(self.anchor());
+                }
+
+                self.set_view(view);
+            },
+            ShowMonth(month) => {
+                if let Ok(date) = self.date.with().month(month as _).day(1).build() {
+                    self.set_date(date);
+                }
+
+                self.set_view(View::Month);
+            },
+            Show   => window.present(),
+            Hide   => window.set_visible(false),
+            Toggle => match window.is_visible() {
+                true  => window.set_visible(false),
+                false => window.present(),
+            },
+            GoTo(date) => {
+                match self.view {
+                    View::Week => self.set_date(date),
+                    _          => self.set_date(date.first_of_month()),
+                }
+
+                self.set_focused(Some(date));
                 self.set_drag(0.0);
             },
+            Reload => {
+                self.refresh_events(&sender);
+                self.refresh_notes(&sender);
+                self.refresh_selected_notes(&sender);
+            },
+            Select(date) => self.select(date, &sender),
+            Key(action) => self.key(action, &sender),
+        }
+
+        if self.changed(Self::date()) {
+            self.refresh_notes(&sender);
+
+            #[cfg(feature = "DBus")]
+            crate::dbus::set_month(self.date);
         }
     }
 
-    async fn update_cmd(&mut self, message: Self::CommandOutput, _: AsyncComponentSender<Self>, _: &Self::Root) {
+    #[allow(unused_variables)]
+    async fn update_cmd(&mut self, message: Self::CommandOutput, sender: AsyncComponentSender<Self>, window: &Self::Root) {
+        self.reset();
+
         match message {
             CommandMessage::SetStyle(style) => relm4::set_global_css(&style),
+            CommandMessage::WatchStyle(paths) => {
+                let (userstyle, settings) = (self.userstyle.clone(), self.style_settings.clone());
+
+                // Imports may have changed with the reload, so the monitors are replaced every time
+                self._style_monitors = Self::watch(&paths, move || Self::load_style(userstyle.clone(), settings.clone(), false, &sender));
+            },
+            CommandMessage::SetEvents(events) => {
+                self.agenda.emit(AgendaMessage::SetEvents(events.clone()));
+                self.set_events(events.into());
+            },
+            CommandMessage::SetToday(date) => {
+                self.agenda.emit(AgendaMessage::SetToday(date));
+                self.set_today(date);
+            },
+            CommandMessage::SetNotes(dates) => self.set_notes(dates),
+            CommandMessage::SetHolidays(holidays) => self.set_holidays(holidays),
+            CommandMessage::SetSelectedNotes(date, notes) => {
+                if self.selected == Some(date) {
+                    self.set_selected_notes(notes);
+                }
+            },
+            #[cfg(feature = "Accent")]
+            CommandMessage::Appearance(change) => match change {
+                crate::accent::Change::Scheme(scheme) => {
+                    window.remove_css_class("dark");
+                    window.remove_css_class("light");
+
+                    if let Some(class) = scheme.class() {
+                        window.add_css_class(class);
+                    }
+                },
+                crate::accent::Change::Accent => if self.style_settings.accent {
+                    Self::load_style(self.userstyle.clone(), self.style_settings.clone(), false, &sender);
+                },
+            },
             CommandMessage::Quit => relm4::main_application().quit(),
         }
     }
@@ -1,8 +1,16 @@
 use std::ffi::CStr;
 
+use jiff::ToSpan;
+use jiff::civil::Date;
+
+use crate::error::CLIError;
+
 pub const MONTHS: [&str; 12] = ["January", "February", "March", "April", "May", "Jun", "July", "August", "September", "October", "November", "December"];
 pub const WEEKDAYS: [&str; 7] = ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
 
+/// Rows of days in a month page, enough for a 31 day month starting on the last day of the week.
+pub const WEEKS: u8 = 6;
+
 pub enum CalendarDay {
     Previous(u8),
     Current(u8),
@@ -19,85 +27,172 @@ impl From<CalendarDay> for u8 {
     }
 }
 
-pub fn day_for(year: u16, month: u8, day: &str) -> impl Fn(u8, u8) -> CalendarDay {
-    debug_assert!(year  != 0);
-    debug_assert!(month != 0);
+/// Days a month is drawn with: six weeks from the first day of the week on or before its first day.
+///
+/// Drawing and date lookups share it, so a cell and the date it stands for can't disagree.
+#[derive(Clone, Copy, Debug)]
+pub struct Page {
+    month: Date,
+    start: Date,
+}
 
-    let weekdays = WEEKDAYS.len() as u8;
+impl Page {
+    pub fn new(year: i16, month: i8, first: u8) -> Self {
+        debug_assert!(is_supported(year));
 
-    let days = days_in_month(year, month);
-    let padding = (weekdays - first_day(day) + day_of_week(year, month, 1)) % weekdays;
+        let weekdays = WEEKDAYS.len() as i8;
 
-    let days_prev = match month == 1 {
-        false => days_in_month(year, month - 1),
-        true  => days_in_month(year - 1, MONTHS.len() as u8),
-    };
+        let month = Date::new(year, month, 1).unwrap();
+        let padding = (month.weekday().to_sunday_zero_offset() + weekdays - first as i8) % weekdays;
 
-    move |column: u8, row: u8| {
-        debug_assert!(column < weekdays);
+        // The week before January of year 1 falls in year 0, which jiff still represents
+        Page { month, start: month - (padding as i64).days() }
+    }
 
-        if (row <= padding / weekdays) && (column < padding) {
-            return CalendarDay::Previous(1 + days_prev + column - padding)
-        }
+    #[inline]
+    fn padding(&self) -> i32 {
+        self.month.since(self.start).unwrap().get_days()
+    }
+
+    #[inline]
+    fn index(column: u8, row: u8) -> i32 {
+        debug_assert!(column < WEEKDAYS.len() as u8);
+        debug_assert!(row < WEEKS);
+
+        row as i32 * WEEKDAYS.len() as i32 + column as i32
+    }
+
+    /// First and last date of the page, the latter limited to the last date jiff supports.
+    pub fn range(&self) -> (Date, Date) {
+        let last = self.start.saturating_add((WEEKS as i64 * WEEKDAYS.len() as i64 - 1).days());
+
+        (self.start, last)
+    }
+
+    pub fn date(&self, column: u8, row: u8) -> Option<Date> {
+        self.start.checked_add(Self::index(column, row).days()).ok()
+    }
 
-        if row * weekdays + column >= days + padding {
-            return CalendarDay::Next(1 + row * weekdays + column - padding - days)
+    /// Column and row of `date`, if it's on the page.
+    pub fn position(&self, date: Date) -> Option<(u8, u8)> {
+        let (first, last) = self.range();
+
+        if date < first || date > last {
+            return None
         }
 
-        CalendarDay::Current(1 + row * weekdays + column - padding)
+        let diff = date.since(first).ok()?.get_days();
+
+        Some(((diff % WEEKDAYS.len() as i32) as u8, (diff / WEEKDAYS.len() as i32) as u8))
+    }
+
+    pub fn day(&self, column: u8, row: u8) -> CalendarDay {
+        let index = Self::index(column, row);
+        let padding = self.padding();
+        let days = self.month.days_in_month() as i32;
+
+        // Counted rather than looked up, as days after December of year 9999 have no dates
+        match index {
+            i if i < padding => CalendarDay::Previous(self.date(column, row).unwrap().day() as u8),
+            i if i < padding + days => CalendarDay::Current((i - padding + 1) as u8),
+            i => CalendarDay::Next((i - padding - days + 1) as u8),
+        }
     }
 }
 
+/// Whether `year` is within the years the calendar can show.
+///
+/// Dates follow the proleptic Gregorian calendar, the same as ISO 8601 and jiff, so years before
+/// the reform are not shifted to the Julian calendar like `cal(1)` does with September 1752.
 #[inline]
-pub fn is_after_reform(year: u16) -> bool {
-    debug_assert!(year  != 0);
+pub fn is_supported(year: i16) -> bool {
+    (FIRST_YEAR as i16..=LAST_YEAR as i16).contains(&year)
+}
 
-    const REFORM_YEAR: u16 = 1752;
-    const _REFORM_MONTH: u8 = 11;
+pub const FIRST_YEAR: u16 = 1;
+pub const LAST_YEAR:  u16 = 9999;
 
-    year > REFORM_YEAR
+/// Date from `YYYY-MM-DD`, or the first day of the month from `YYYY-MM`.
+pub fn parse_date(s: &str) -> Option<Date> {
+    s.parse::<Date>().ok()
+        .or_else(|| format!("{s}-01").parse().ok())
+        .filter(|date| is_supported(date.year()))
 }
 
-pub fn is_leap_year(year: u16) -> bool {
-    debug_assert!(year  != 0);
+/// Index of the first day of the week, an empty string stands for the one from `LC_TIME`.
+#[inline]
+pub fn first_day(day: &str) -> u8 {
+    match day.is_empty() {
+        true  => locale_first_day(),
+        false => weekday_index(day).unwrap_or(0),
+    }
+}
 
-    debug_assert!(is_after_reform(year));
+/// Index of a weekday from its full or shortened name: (sun)day, (mon)day...
+pub fn weekday_index(day: &str) -> Option<u8> {
+    let day = day.to_lowercase();
 
-    (year % 4 == 0) && (year % 100 != 0) || (year % 400 == 0)
-}
+    if day.is_empty() {
+        return None
+    }
 
-pub fn days_in_month(year: u16, month: u8) -> u8 {
-    debug_assert!(year  != 0);
-    debug_assert!(month != 0);
+    WEEKDAYS.iter()
+        .position(|e| e.to_lowercase().starts_with(&day))
+        .map(|i| i as u8)
+}
 
-    const DAYS:      [u8; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
-    const DAYS_LEAP: [u8; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+/// Bitmask of weekend days, where bit 0 is Sunday.
+pub fn weekend(days: &[String]) -> Result<u8, CLIError> {
+    days.iter().try_fold(0, |mask, day| {
+        weekday_index(day)
+            .map(|i| mask | 1 << i)
+            .ok_or_else(|| CLIError::Weekday(day.to_owned()))
+    })
+}
 
-    match is_leap_year(year) {
-        false => DAYS      [month as usize - 1],
-        true  => DAYS_LEAP [month as usize - 1],
-    }
+#[inline]
+pub fn is_weekend(mask: u8, day: u8) -> bool {
+    mask & (1 << (day % WEEKDAYS.len() as u8)) != 0
 }
 
-pub fn day_of_week(mut year: u16, month: u8, day: u8) -> u8 {
-    debug_assert!(year  != 0);
-    debug_assert!(month != 0);
-    debug_assert!(day   != 0);
+// glibc
+const _NL_TIME_WEEK_1STDAY:   libc::nl_item = 0x20066;
+const _NL_TIME_FIRST_WEEKDAY: libc::nl_item = 0x20068;
+const _NL_TIME_FIRST_WORKDAY: libc::nl_item = 0x20069;
 
-    debug_assert!(is_after_reform(year));
+// `_NL_TIME_FIRST_WEEKDAY` and `_NL_TIME_FIRST_WORKDAY` are 1-based offsets from `_NL_TIME_WEEK_1STDAY`,
+// which itself is a date like 19971130 (Sunday) or 19971201 (Monday).
+fn locale_day(item: libc::nl_item) -> Option<u8> {
+    let (week_1stday, offset) = unsafe {
+        let week_1stday = libc::nl_langinfo(_NL_TIME_WEEK_1STDAY) as usize as u32;
+        let offset = *libc::nl_langinfo(item) as u8;
 
-    const OFFSETS: [u8; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+        (week_1stday, offset)
+    };
 
-    year -= (month < 3) as u16;
+    let (year, month, day) = (week_1stday / 10000, week_1stday / 100 % 100, week_1stday % 100);
 
-    ((year + year / 4 - year / 100 + year / 400 + (OFFSETS[month as usize - 1] + day) as u16) % WEEKDAYS.len() as u16) as u8
+    if year == 0 || !(1..=12).contains(&month) || day == 0 || offset == 0 {
+        return None
+    }
+
+    let base = Date::new(year as i16, month as i8, day as i8).ok()?.weekday().to_sunday_zero_offset() as u8;
+
+    Some((base + offset - 1) % WEEKDAYS.len() as u8)
 }
 
-#[inline]
-pub fn first_day(day: &str) -> u8 {
-    WEEKDAYS.iter()
-        .position(|e| e.to_lowercase().find(day) == Some(0))
-        .unwrap_or(0) as u8
+pub fn locale_first_day() -> u8 {
+    locale_day(_NL_TIME_FIRST_WEEKDAY).unwrap_or(0)
+}
+
+/// Two days preceding the first workday of `LC_TIME`, Saturday and Sunday if it's unknown.
+pub fn locale_weekend() -> u8 {
+    let weekdays = WEEKDAYS.len() as u8;
+
+    match locale_day(_NL_TIME_FIRST_WORKDAY) {
+        Some(day) => 1 << ((day + weekdays - 2) % weekdays) | 1 << ((day + weekdays - 1) % weekdays),
+        None      => 1 << 6 | 1 << 0,
+    }
 }
 
 pub fn weekdays_with_first(day: &str) -> [String; WEEKDAYS.len()] {
@@ -149,3 +244,114 @@ pub fn monthname(month: u8) -> String {
         // }
     // }
 // }
+
+#[cfg(test)]
+mod tests {
+    use jiff::civil::{date, Date};
+
+    use super::*;
+
+    fn current_days(page: &Page) -> usize {
+        (0..WEEKS)
+            .flat_map(|row| (0..WEEKDAYS.len() as u8).map(move |column| (column, row)))
+            .filter(|&(column, row)| matches!(page.day(column, row), CalendarDay::Current(_)))
+            .count()
+    }
+
+    // Every page from year 1 to 9999 with every first day of the week, checked against jiff
+    #[test]
+    fn pages() {
+        for year in FIRST_YEAR as i16..=LAST_YEAR as i16 {
+            for month in 1..=12 {
+                let first_of_month = date(year, month, 1);
+
+                for first in 0..WEEKDAYS.len() as u8 {
+                    let page = Page::new(year, month, first);
+                    let padding = (first_of_month.weekday().to_sunday_zero_offset() - first as i8).rem_euclid(7);
+
+                    let mut previous = 0;
+                    let mut first_current = None;
+
+                    for row in 0..WEEKS {
+                        for column in 0..WEEKDAYS.len() as u8 {
+                            let date = page.date(column, row);
+
+                            if let Some(date) = date {
+                                assert_eq!(page.position(date), Some((column, row)), "{year}-{month} {first}: {date}");
+                            }
+
+                            match page.day(column, row) {
+                                CalendarDay::Previous(day) => {
+                                    previous += 1;
+                                    assert_eq!(date.map(|d| (d.day() as u8, d < first_of_month)), Some((day, true)));
+                                },
+                                CalendarDay::Current(day) => {
+                                    first_current.get_or_insert(date);
+                                    assert_eq!(date.map(|d| (d.day() as u8, d.first_of_month())), Some((day, first_of_month)));
+                                },
+                                CalendarDay::Next(day) => {
+                                    // Days after December of year 9999 have no date
+                                    if let Some(date) = date {
+                                        assert_eq!(date.day() as u8, day);
+                                        assert!(date > first_of_month.last_of_month());
+                                    }
+                                },
+                            }
+                        }
+                    }
+
+                    assert_eq!(first_current, Some(Some(first_of_month)), "{year}-{month} {first}");
+                    assert_eq!(previous, padding, "{year}-{month} {first}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn january_of_year_one() {
+        // January 1 of year 1 is a Monday
+        let page = Page::new(1, 1, 1);
+        assert_eq!(page.range().0, date(1, 1, 1));
+
+        let page = Page::new(1, 1, 0);
+        assert_eq!(page.range().0, date(0, 12, 31));
+        assert!(matches!(page.day(0, 0), CalendarDay::Previous(31)));
+        assert!(matches!(page.day(1, 0), CalendarDay::Current(1)));
+        assert_eq!(page.position(date(1, 1, 1)), Some((1, 0)));
+    }
+
+    #[test]
+    fn december_of_year_9999() {
+        for first in 0..WEEKDAYS.len() as u8 {
+            let page = Page::new(9999, 12, first);
+
+            assert_eq!(page.range().1, Date::MAX);
+            assert_eq!(page.date(6, WEEKS - 1), None);
+            assert!(matches!(page.day(6, WEEKS - 1), CalendarDay::Next(_)));
+            assert!(page.position(Date::MAX).is_some());
+            assert_eq!(current_days(&page), 31);
+        }
+    }
+
+    #[test]
+    fn leap_years() {
+        for (year, days) in [(1600, 29), (1700, 28), (1900, 28), (2000, 29)] {
+            assert_eq!(current_days(&Page::new(year, 2, 0)), days, "February {year}");
+        }
+    }
+
+    #[test]
+    fn supported_range() {
+        assert!(!is_supported(0));
+        assert!(is_supported(1));
+        assert!(is_supported(9999));
+        assert!(!is_supported(10000));
+
+        assert_eq!(parse_date("0000-01-01"), None);
+        assert_eq!(parse_date("0000-12"), None);
+        assert_eq!(parse_date("10000-01-01"), None);
+        assert_eq!(parse_date("+010000-01"), None);
+        assert_eq!(parse_date("0001-01"), Some(date(1, 1, 1)));
+        assert_eq!(parse_date("9999-12-31"), Some(date(9999, 12, 31)));
+    }
+}
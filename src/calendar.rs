@@ -0,0 +1,94 @@
+use jiff::civil::Date;
+
+/// A date in a calendar other than the Gregorian one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AltDate {
+    pub year:  i32,
+    pub month: u8,
+    pub day:   u8,
+}
+
+/// A calendar shown next to the Gregorian one, as day numbers under each day and month names in the header.
+pub trait System: Sync {
+    fn name(&self) -> &'static str;
+
+    /// Same day in this calendar, none if it precedes its epoch.
+    fn date(&self, date: Date) -> Option<AltDate>;
+
+    fn monthname(&self, month: u8) -> &'static str;
+}
+
+const SYSTEMS: [&dyn System; 1] = [&Hijri];
+
+pub fn system(name: &str) -> Option<&'static dyn System> {
+    SYSTEMS.into_iter().find(|system| system.name() == name.to_lowercase())
+}
+
+pub fn names() -> impl Iterator<Item = &'static str> {
+    SYSTEMS.into_iter().map(|system| system.name())
+}
+
+/// Month names of the days from `first` to `last`: "Ramadan 1447", "Ramadan – Shawwal 1447"
+/// or "Dhu al-Hijjah 1447 – Muharram 1448".
+pub fn monthnames(system: &dyn System, first: Date, last: Date) -> Option<String> {
+    let (first, last) = (system.date(first)?, system.date(last)?);
+
+    let s = match (first.year == last.year, first.month == last.month) {
+        (true, true)  => format!("{} {}", system.monthname(first.month), first.year),
+        (true, false) => format!("{} – {} {}", system.monthname(first.month), system.monthname(last.month), last.year),
+        (false, _)    => format!("{} {} – {} {}", system.monthname(first.month), first.year, system.monthname(last.month), last.year),
+    };
+
+    Some(s)
+}
+
+// Julian day number, counted from noon of January 1, 4713 BC in the Julian calendar
+fn julian_day(date: Date) -> i32 {
+    const UNIX_EPOCH: i32 = 2440588;
+
+    UNIX_EPOCH + date.since(Date::constant(1970, 1, 1)).unwrap().get_days()
+}
+
+/// Tabular Islamic calendar, 30 year cycles with 11 leap years and months alternating between 30 and 29 days.
+///
+/// It's arithmetical and can be a day or two off from calendars that follow the sighting of the moon.
+pub struct Hijri;
+
+impl System for Hijri {
+    fn name(&self) -> &'static str {
+        "hijri"
+    }
+
+    fn date(&self, date: Date) -> Option<AltDate> {
+        // July 16, 622 in the Julian calendar
+        const EPOCH: i32 = 1948440;
+
+        let jd = julian_day(date);
+
+        if jd < EPOCH {
+            return None
+        }
+
+        let l = jd - EPOCH + 10632;
+        let n = (l - 1) / 10631;
+        let l = l - 10631 * n + 354;
+        let j = ((10985 - l) / 5316) * ((50 * l) / 17719) + (l / 5670) * ((43 * l) / 15238);
+        let l = l - ((30 - j) / 15) * ((17719 * j) / 50) - (j / 16) * ((15238 * j) / 43) + 29;
+        let month = (24 * l) / 709;
+        let day = l - (709 * month) / 24;
+        let year = 30 * n + j - 30;
+
+        Some(AltDate { year, month: month as u8, day: day as u8 })
+    }
+
+    fn monthname(&self, month: u8) -> &'static str {
+        const MONTHS: [&str; 12] = [
+            "Muharram", "Safar", "Rabiʻ I", "Rabiʻ II", "Jumada I", "Jumada II",
+            "Rajab", "Shaʻban", "Ramadan", "Shawwal", "Dhu al-Qiʻdah", "Dhu al-Hijjah",
+        ];
+
+        debug_assert!(month != 0);
+
+        MONTHS[month as usize - 1]
+    }
+}
@@ -0,0 +1,86 @@
+use std::io;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+use jiff::civil::Date;
+use jiff::{Timestamp, ToSpan, Zoned};
+
+use crate::error::ClockError;
+
+// jiff caches the system time zone for 5 minutes, checking as often picks up time zone changes
+const RECHECK: i64 = 5;
+
+/// Calls `f` with the new date every time the local date changes, until it returns `false`.
+///
+/// Waits on the realtime clock, so it wakes up at midnight even after a suspend and right after
+/// the clock is set, rather than sleeping for a fixed duration.
+pub fn on_day_change(mut today: Date, f: impl Fn(Date) -> bool + Send + 'static) -> Result<(), ClockError> {
+    let timer = Timer::new().map_err(ClockError::Timer)?;
+
+    std::thread::spawn(move || loop {
+        let now = Zoned::now();
+
+        if now.date() != today {
+            today = now.date();
+
+            if !f(today) { return }
+        }
+
+        let midnight = now.tomorrow()
+            .and_then(|tomorrow| tomorrow.start_of_day())
+            .map(|midnight| midnight.timestamp())
+            .unwrap_or(Timestamp::MAX);
+
+        let recheck = now.timestamp().saturating_add(RECHECK.minutes()).unwrap_or(Timestamp::MAX);
+
+        if let Err(e) = timer.wait_until(midnight.min(recheck)) {
+            eprintln!("{}", ClockError::Timer(e));
+            return
+        }
+    });
+
+    Ok(())
+}
+
+struct Timer(OwnedFd);
+
+impl Timer {
+    fn new() -> io::Result<Self> {
+        let fd = unsafe { libc::timerfd_create(libc::CLOCK_REALTIME, libc::TFD_CLOEXEC) };
+
+        if fd < 0 {
+            return Err(io::Error::last_os_error())
+        }
+
+        Ok(Timer(unsafe { OwnedFd::from_raw_fd(fd) }))
+    }
+
+    // Blocks until `time` or until the system clock is set, whichever comes first
+    fn wait_until(&self, time: Timestamp) -> io::Result<()> {
+        let spec = libc::itimerspec {
+            it_interval: libc::timespec { tv_sec: 0, tv_nsec: 0 },
+            it_value:    libc::timespec { tv_sec: time.as_second() as _, tv_nsec: time.subsec_nanosecond() as _ },
+        };
+
+        let flags = libc::TFD_TIMER_ABSTIME | libc::TFD_TIMER_CANCEL_ON_SET;
+
+        if unsafe { libc::timerfd_settime(self.0.as_raw_fd(), flags, &spec, std::ptr::null_mut()) } < 0 {
+            return Err(io::Error::last_os_error())
+        }
+
+        let mut expirations = 0u64;
+        let size = std::mem::size_of::<u64>();
+
+        let n = unsafe { libc::read(self.0.as_raw_fd(), &mut expirations as *mut u64 as *mut libc::c_void, size) };
+
+        if n == size as isize {
+            return Ok(())
+        }
+
+        let e = io::Error::last_os_error();
+
+        match e.raw_os_error() {
+            Some(libc::ECANCELED | libc::EINTR) => Ok(()),
+            _ => Err(e),
+        }
+    }
+}
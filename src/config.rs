@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+use crate::error::ConfigError;
+
+pub struct Entry {
+    pub section: String,
+    pub key:     String,
+    pub value:   String,
+}
+
+pub fn path() -> PathBuf {
+    let mut path = crate::xdg::config_dir();
+    path.push(crate::APP_BINARY);
+    path.push("config.ini");
+    path
+}
+
+/// Reads `key = value` pairs grouped under `[section]` headers, a missing file is not an error.
+pub fn read() -> Result<Vec<Entry>, ConfigError> {
+    let path = path();
+
+    let s = match std::fs::read_to_string(&path) {
+        Ok(s) => s,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(ConfigError::Read { e, path }),
+    };
+
+    let mut entries = Vec::new();
+    let mut section = String::new();
+
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with(['#', ';']) {
+            continue
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            section = name.trim().to_lowercase();
+            continue
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(ConfigError::Parse { line: i + 1, path })
+        };
+
+        entries.push(Entry {
+            section: section.clone(),
+            key:     key.trim().to_owned(),
+            value:   value.trim().to_owned(),
+        });
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,203 @@
+use std::sync::OnceLock;
+
+use jiff::civil::Date;
+
+use relm4::Sender;
+
+use crate::app::ElementMessage;
+use crate::cal;
+use crate::error::{Error, ZbusError};
+
+pub const NAME: &str = "elvy.caffi.Control";
+pub const PATH: &str = "/elvy/caffi/Control";
+
+static CONNECTION: OnceLock<zbus::Connection> = OnceLock::new();
+
+/// Control interface of the running widget, for bars and scripts:
+/// `busctl --user call elvy.caffi.Control /elvy/caffi/Control elvy.caffi.Control Toggle`
+pub struct Control {
+    sender: Sender<ElementMessage>,
+    month:  Date,
+}
+
+#[zbus::interface(name = "elvy.caffi.Control")]
+impl Control {
+    async fn show(&self) {
+        self.sender.emit(ElementMessage::Show);
+    }
+
+    async fn hide(&self) {
+        self.sender.emit(ElementMessage::Hide);
+    }
+
+    async fn toggle(&self) {
+        self.sender.emit(ElementMessage::Toggle);
+    }
+
+    /// Takes `YYYY-MM` or `YYYY-MM-DD`.
+    async fn goto_date(&self, date: &str) -> zbus::fdo::Result<()> {
+        let date = cal::parse_date(date)
+            .ok_or_else(|| zbus::fdo::Error::InvalidArgs(format!("'{date}' is not a valid date, expected YYYY-MM or YYYY-MM-DD")))?;
+
+        self.sender.emit(ElementMessage::GoTo(date));
+
+        Ok(())
+    }
+
+    async fn next_month(&self) {
+        self.sender.emit(ElementMessage::NextMonth);
+    }
+
+    async fn prev_month(&self) {
+        self.sender.emit(ElementMessage::PrevMonth);
+    }
+
+    /// Reads events and notes again.
+    async fn reload(&self) {
+        self.sender.emit(ElementMessage::Reload);
+    }
+
+    /// Shown month as `YYYY-MM`.
+    #[zbus(property)]
+    async fn current_month(&self) -> String {
+        self.month.strftime("%Y-%m").to_string()
+    }
+}
+
+/// Takes the name on the session bus and exports `Control` for as long as the app runs.
+pub async fn serve(sender: Sender<ElementMessage>, month: Date) -> Result<(), Error> {
+    let connection = connect(zbus::connection::Builder::session(), sender, month).await?;
+    let _ = CONNECTION.set(connection);
+
+    Ok(())
+}
+
+async fn connect(builder: zbus::Result<zbus::connection::Builder<'_>>, sender: Sender<ElementMessage>, month: Date) -> Result<zbus::Connection, Error> {
+    let connection = builder
+        .and_then(|builder| builder.name(NAME))
+        .and_then(|builder| builder.serve_at(PATH, Control { sender, month: month.first_of_month() }))
+        .map_err(|e| ZbusError::Serve { e })?
+        .build()
+        .await
+        .map_err(|e| ZbusError::Serve { e })?;
+
+    Ok(connection)
+}
+
+/// Updates `CurrentMonth` and notifies its listeners.
+pub fn set_month(month: Date) {
+    let Some(connection) = CONNECTION.get().cloned() else { return };
+
+    relm4::spawn(async move {
+        if let Err(e) = update_month(&connection, month).await {
+            eprintln!("{}", ZbusError::Emit { e });
+        }
+    });
+}
+
+async fn update_month(connection: &zbus::Connection, month: Date) -> zbus::Result<()> {
+    let iface = connection.object_server().interface::<_, Control>(PATH).await?;
+    let month = month.first_of_month();
+
+    if iface.get().await.month == month { return Ok(()) }
+
+    iface.get_mut().await.month = month;
+
+    let control = iface.get().await;
+    control.current_month_changed(iface.signal_emitter()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+
+    use jiff::ToSpan;
+    use jiff::civil::date;
+
+    use zbus::proxy::CacheProperties;
+
+    use super::*;
+
+    #[zbus::proxy(interface = "elvy.caffi.Control", default_service = "elvy.caffi.Control", default_path = "/elvy/caffi/Control")]
+    trait Remote {
+        fn goto_date(&self, date: &str) -> zbus::Result<()>;
+
+        fn next_month(&self) -> zbus::Result<()>;
+
+        #[zbus(property)]
+        fn current_month(&self) -> zbus::Result<String>;
+    }
+
+    /// Private session bus, stopped when dropped.
+    struct Bus {
+        daemon:  Child,
+        address: String,
+    }
+
+    impl Bus {
+        // `None` when `dbus-daemon` can't be started
+        fn start() -> Option<Self> {
+            let daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .spawn();
+
+            let mut daemon = match daemon {
+                Ok(daemon) => daemon,
+                Err(e) => {
+                    eprintln!("Skipping, unable to start dbus-daemon\n{e}");
+                    return None
+                }
+            };
+
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take().unwrap()).read_line(&mut address).unwrap();
+
+            Some(Bus { daemon, address: address.trim().to_owned() })
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    #[tokio::test]
+    async fn control() {
+        let Some(bus) = Bus::start() else { return };
+        let (sender, receiver) = relm4::channel();
+
+        let server = connect(zbus::connection::Builder::address(bus.address.as_str()), sender, date(2026, 10, 18)).await.unwrap();
+        let client = zbus::connection::Builder::address(bus.address.as_str()).unwrap().build().await.unwrap();
+
+        let remote = RemoteProxy::builder(&client)
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await
+            .unwrap();
+
+        assert_eq!(remote.current_month().await.unwrap(), "2026-10");
+
+        // The app answers `GoTo` and `NextMonth` by showing the month and calling `set_month`
+        remote.goto_date("2026-12").await.unwrap();
+        let Some(ElementMessage::GoTo(month)) = receiver.recv().await else { panic!("expected GoTo") };
+        assert_eq!(month, date(2026, 12, 1));
+
+        update_month(&server, month).await.unwrap();
+        assert_eq!(remote.current_month().await.unwrap(), "2026-12");
+
+        remote.next_month().await.unwrap();
+        assert!(matches!(receiver.recv().await, Some(ElementMessage::NextMonth)));
+
+        update_month(&server, month.saturating_add(1.month())).await.unwrap();
+        assert_eq!(remote.current_month().await.unwrap(), "2027-01");
+
+        match remote.goto_date("2026-13").await {
+            Err(zbus::Error::MethodError(name, _, _)) => assert_eq!(name.as_str(), "org.freedesktop.DBus.Error.InvalidArgs"),
+            result => panic!("expected InvalidArgs, got {result:?}"),
+        }
+    }
+}
@@ -25,9 +25,21 @@ pub enum Error {
     #[error(transparent)]
     Cache(#[from] CacheError),
 
-    #[cfg(feature = "Accent")]
     #[error(transparent)]
-    Accent(#[from] ZbusError),
+    Note(#[from] NoteError),
+
+    #[error(transparent)]
+    Ics(#[from] IcsError),
+
+    #[error(transparent)]
+    Holiday(#[from] HolidayError),
+
+    #[error(transparent)]
+    Clock(#[from] ClockError),
+
+    #[cfg(any(feature = "Accent", feature = "DBus"))]
+    #[error(transparent)]
+    Zbus(#[from] ZbusError),
 }
 
 impl Debug for Error {
@@ -40,6 +52,24 @@ impl Debug for Error {
 pub enum CLIError {
     #[error("'{0}' is not a valid anchor point")]
     Anchor(String),
+
+    #[error("'{0}' is not a valid view")]
+    View(String),
+
+    #[error("'{0}' is not a valid day of the week")]
+    Weekday(String),
+
+    #[error("'{0}' is not a valid date, expected YYYY-MM or YYYY-MM-DD")]
+    Date(String),
+
+    #[error("'{0}' is not a known calendar, expected one of: {names}", names = crate::calendar::names().collect::<Vec<_>>().join(", "))]
+    Calendar(String),
+
+    #[error("'{0}' is not a valid CSS variable, expected name=value")]
+    Var(String),
+
+    #[error("'{0}' is not a valid note, expected `YYYY-MM-DD [HH:MM] text`")]
+    Note(String),
 }
 
 #[derive(Error, Debug)]
@@ -52,6 +82,18 @@ pub enum ConfigError {
 
     #[error("Unable to access a config directory\n{0} is not a directory")]
     NotDirectory(PathBuf),
+
+    #[error("Expected `key = value` on line {line} ({path})")]
+    Parse { line: usize, path: PathBuf },
+
+    #[error("'{0}' is not a valid action")]
+    Action(String),
+
+    #[error("'{0}' is not a valid CSS variable name")]
+    Var(String),
+
+    #[error("'{0}' is not a valid key")]
+    Key(String),
 }
 
 #[derive(Error, Debug)]
@@ -65,12 +107,6 @@ pub enum StyleError {
     #[error("Unable to read a style file ({path})\n{e}")]
     Read { e: io::Error, path: PathBuf },
 
-    #[error("Error while trying to get metadata ({path})\n{e}")]
-    Meta { e: io::Error, path: PathBuf },
-
-    #[error("Unable to read mtime of a style ({path})\n{e}")]
-    MTime { e: io::Error, path: PathBuf},
-
     #[error("Unable to write a style to a file ({path})\n{e}")]
     Write { e: io::Error, path: PathBuf },
 
@@ -88,6 +124,9 @@ pub enum StyleError {
 
 #[derive(Error, Debug)]
 pub enum CacheError {
+    #[error("Unable to create the cache directory ({path})\n{e}")]
+    Dir { e: io::Error, path: PathBuf },
+
     #[error("Unable to create a cache file ({path})\n{e}")]
     Create { e: io::Error, path: PathBuf },
 
@@ -97,22 +136,80 @@ pub enum CacheError {
     #[error("Unable to write a cache file ({path})\n{e}")]
     Write { e: io::Error, path: PathBuf },
 
-    #[error("Unable to update mtime for cache ({path})\n{e}")]
-    MTime { e: io::Error, path: PathBuf },
+    #[error("Unable to update the modification time of a cache file ({path})\n{e}")]
+    Touch { e: io::Error, path: PathBuf },
 }
 
-#[cfg(feature = "Accent")]
+#[derive(Error, Debug)]
+pub enum NoteError {
+    #[error("Unable to create a notes file or directory ({path})\n{e}")]
+    Create { e: io::Error, path: PathBuf },
+
+    #[error("Unable to read notes ({path})\n{e}")]
+    Read { e: io::Error, path: PathBuf },
+
+    #[error("Unable to write notes ({path})\n{e}")]
+    Write { e: io::Error, path: PathBuf },
+
+    #[error("Malformed note on line {line} ({path})")]
+    Parse { line: usize, path: PathBuf },
+}
+
+#[derive(Error, Debug)]
+pub enum IcsError {
+    #[error("Unable to read a calendar file ({path})\n{e}")]
+    Read { e: io::Error, path: PathBuf },
+
+    #[error("Malformed calendar file on line {line}: {reason} ({path})")]
+    Parse { line: usize, reason: &'static str, path: PathBuf },
+}
+
+#[derive(Error, Debug)]
+pub enum HolidayError {
+    #[error("Unable to read a holidays file ({path})\n{e}")]
+    Read { e: io::Error, path: PathBuf },
+
+    #[error("Malformed holidays file on line {line}, expected `MM-DD`, `easter+N` or `MM/weekday/N` followed by a name ({path})")]
+    Parse { line: usize, path: PathBuf },
+
+    #[error("'{0}' is neither a holidays file nor a known region")]
+    Region(String),
+}
+
+#[derive(Error, Debug)]
+pub enum ClockError {
+    #[error("Unable to wait for midnight, today won't be updated\n{0}")]
+    Timer(io::Error),
+}
+
+#[cfg(any(feature = "Accent", feature = "DBus"))]
 #[derive(Error, Debug)]
 pub enum ZbusError {
+    #[cfg(feature = "Accent")]
     #[error("Couldn't establish a connection with the session bus\n{e}")]
     Connect { e: zbus::Error },
 
+    #[cfg(feature = "Accent")]
     #[error("Couldn't create a proxy to access the bus interface\n{e}")]
     Proxy { e: zbus::Error },
 
+    #[cfg(feature = "Accent")]
     #[error("Unable to read `{key}` from `{namespace}, make sure that your `xdg-desktop-portal` supports it and configured correctly`\n{e}")]
     Read { e: zbus::Error, namespace: String, key: String },
 
+    #[cfg(feature = "Accent")]
     #[error("Unable to parse unexpected result from the portal\n{v}")]
-    BadResult { v: String }
+    BadResult { v: String },
+
+    #[cfg(feature = "Accent")]
+    #[error("Couldn't subscribe to changes of the system's appearance settings\n{e}")]
+    Subscribe { e: zbus::Error },
+
+    #[cfg(feature = "DBus")]
+    #[error("Couldn't export the control interface on the session bus, is another instance running?\n{e}")]
+    Serve { e: zbus::Error },
+
+    #[cfg(feature = "DBus")]
+    #[error("Unable to notify about a change of the shown month\n{e}")]
+    Emit { e: zbus::Error },
 }
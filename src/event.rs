@@ -1,23 +1,99 @@
 use std::borrow::Cow;
 
-use jiff::{Timestamp, Zoned};
-
-pub fn today() -> Event {
-    Event {
-        active: true,
-        class: "today".into(),
-        start: Zoned::now().timestamp(),
-        end: None,
-        repeat: Repeat::Never,
-    }
-}
+use jiff::civil::{Date, Time};
+use jiff::tz::TimeZone;
+use jiff::{Timestamp, ToSpan, Unit, Zoned};
+
+use crate::recur::Rule;
 
+#[derive(Debug, Clone)]
 pub struct Event {
     pub active: bool,
     pub class:  Cow<'static, str>,
+    pub summary: String,
+    pub description: String,
     pub start:  Timestamp,
     pub end:    Option<Timestamp>,
-    pub repeat: Repeat,
+    // Of DTSTART, the rule repeats the time of day in it
+    pub time_zone: TimeZone,
+    pub rule:   Option<Rule>,
+    pub exdates: Vec<Timestamp>,
+}
+
+impl Event {
+    /// Start and end of every occurrence that overlaps the days `from..=to`, in the local time zone.
+    pub fn spans(&self, from: Date, to: Date) -> Vec<(Zoned, Zoned)> {
+        let start = self.start.to_zoned(self.time_zone.clone());
+
+        // In calendar days, a one-day event stays one day long across a DST change
+        let span = self.end
+            .and_then(|end| start.until((Unit::Day, &end.to_zoned(self.time_zone.clone()))).ok());
+
+        // Occurrences are found in the event's time zone, where a day may end a day later than the local one
+        let starts = match &self.rule {
+            Some(rule) => rule.expand(&start, to.saturating_add(1.day())),
+            None       => vec![start],
+        };
+
+        let excluded: Vec<Date> = self.exdates.iter()
+            .map(|t| t.to_zoned(self.time_zone.clone()).date())
+            .collect();
+
+        let local = TimeZone::system();
+
+        starts.into_iter()
+            .filter(|z| !excluded.contains(&z.date()))
+            .map(|z| {
+                let end = span
+                    .and_then(|span| z.checked_add(span).ok())
+                    .unwrap_or_else(|| z.clone());
+
+                (z.with_time_zone(local.clone()), end.with_time_zone(local.clone()))
+            })
+            .filter(|(start, end)| start.date() <= to && Self::last_day(start, end) >= from)
+            .collect()
+    }
+
+    /// First and last day of every occurrence that overlaps `from..=to`.
+    pub fn occurrences(&self, from: Date, to: Date) -> Vec<(Date, Date)> {
+        self.spans(from, to).iter()
+            .map(|(start, end)| (start.date(), Self::last_day(start, end)))
+            .collect()
+    }
+
+    pub fn is_all_day(&self) -> bool {
+        let start = self.start.to_zoned(TimeZone::system());
+        let end = self.end.map(|end| end.to_zoned(TimeZone::system()));
+
+        start.time() == Time::midnight() && end.is_none_or(|end| end.time() == Time::midnight())
+    }
+
+    /// Part of an occurrence that falls on `date` in the local time zone, `None` when it takes the whole day.
+    pub fn time_range_on(&self, start: &Zoned, end: &Zoned, date: Date) -> Option<String> {
+        if self.is_all_day() {
+            return None
+        }
+
+        let from = (start.date() == date).then(|| start.strftime("%H:%M").to_string());
+        let to = (end.date() == date && end != start).then(|| end.strftime("%H:%M").to_string());
+
+        match (from, to) {
+            (Some(from), Some(to)) => Some(format!("{from}–{to}")),
+            (Some(from), None) if start == end => Some(from),
+            (Some(from), None) => Some(format!("{from}–")),
+            (None, Some(to))   => Some(format!("–{to}")),
+            (None, None)       => None,
+        }
+    }
+
+    /// Last day touched by an occurrence, DTEND is exclusive so an event that ends at midnight doesn't reach that day.
+    pub fn last_day(start: &Zoned, end: &Zoned) -> Date {
+        if end.date() > start.date() && end.time() == Time::midnight() {
+            return end.date().yesterday().unwrap_or(start.date())
+        }
+
+        end.date().max(start.date())
+    }
 }
 
 impl Default for Event {
@@ -25,22 +101,13 @@ impl Default for Event {
         Self {
             active: true,
             class:  "event".into(),
+            summary: String::new(),
+            description: String::new(),
             start:  Default::default(),
             end:    None,
-            repeat: Repeat::Never
+            time_zone: TimeZone::system(),
+            rule:   None,
+            exdates: Vec::new(),
         }
     }
 }
-
-pub enum Repeat {
-    Never,
-    Weekly,
-    Monthly,
-    Yearly,
-}
-
-impl Default for Repeat {
-    fn default() -> Self {
-        Self::Never
-    }
-}
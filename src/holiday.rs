@@ -0,0 +1,164 @@
+use std::path::{Path, PathBuf};
+
+use jiff::civil::{Date, Weekday};
+use jiff::ToSpan;
+
+use tokio::fs;
+
+use crate::cal;
+use crate::error::{Error, HolidayError};
+
+const BUNDLED: [(&str, &str); 3] = [
+    ("de", include_str!("../data/holidays/de.txt")),
+    ("gb", include_str!("../data/holidays/gb.txt")),
+    ("us", include_str!("../data/holidays/us.txt")),
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Rule {
+    Fixed { month: i8, day: i8 },
+    Easter(i16),
+    Weekday { month: i8, weekday: Weekday, nth: i8 },
+}
+
+#[derive(Debug, Clone)]
+pub struct Holiday {
+    pub name: String,
+    rule: Rule,
+}
+
+impl Holiday {
+    pub fn date(&self, year: i16) -> Option<Date> {
+        match self.rule {
+            Rule::Fixed { month, day } => Date::new(year, month, day).ok(),
+            Rule::Easter(offset) => easter(year)?.checked_add(offset.days()).ok(),
+            Rule::Weekday { month, weekday, nth } => {
+                Date::new(year, month, 1).ok()?.nth_weekday_of_month(nth, weekday).ok()
+            },
+        }
+    }
+}
+
+/// Holidays of every year from `from` to `to`, with their dates.
+pub fn between(holidays: &[Holiday], from: Date, to: Date) -> Vec<(Date, &Holiday)> {
+    (from.year()..=to.year())
+        .flat_map(|year| holidays.iter().filter_map(move |h| h.date(year).map(|date| (date, h))))
+        .filter(|(date, _)| (from..=to).contains(date))
+        .collect()
+}
+
+// Anonymous Gregorian algorithm, also known as Meeus/Jones/Butcher
+fn easter(year: i16) -> Option<Date> {
+    let y = year as i32;
+
+    let a = y % 19;
+    let b = y / 100;
+    let c = y % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+
+    Date::new(year, month as i8, day as i8).ok()
+}
+
+/// Reads holidays of a region, which is either a path to a file, a file in the config directory
+/// (`holidays/<region>.txt`) or one of the bundled regions.
+pub async fn read(region: &str) -> Result<Vec<Holiday>, Error> {
+    let path = Path::new(region);
+
+    if path.extension().is_some() || path.components().count() > 1 {
+        return read_file(path).await
+    }
+
+    let user = user_path(region);
+
+    if fs::try_exists(&user).await.unwrap_or(false) {
+        return read_file(&user).await
+    }
+
+    let (_, s) = BUNDLED.iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(region))
+        .ok_or_else(|| HolidayError::Region(region.to_owned()))?;
+
+    parse(s).map_err(|line| HolidayError::Parse { line, path: PathBuf::from(region) }.into())
+}
+
+fn user_path(region: &str) -> PathBuf {
+    let mut path = crate::xdg::config_dir();
+    path.push(crate::APP_BINARY);
+    path.push("holidays");
+    path.push(region.to_lowercase());
+    path.set_extension("txt");
+    path
+}
+
+async fn read_file(path: &Path) -> Result<Vec<Holiday>, Error> {
+    let s = fs::read_to_string(path).await
+        .map_err(|e| HolidayError::Read { e, path: path.to_owned() })?;
+
+    parse(&s).map_err(|line| HolidayError::Parse { line, path: path.to_owned() }.into())
+}
+
+// `<rule> <name>` per line, where the rule is `MM-DD`, `easter±N` or `MM/weekday/N`
+fn parse(s: &str) -> Result<Vec<Holiday>, usize> {
+    let mut holidays = Vec::new();
+
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue
+        }
+
+        let (rule, name) = line.split_once(char::is_whitespace).ok_or(i + 1)?;
+        let rule = parse_rule(rule).ok_or(i + 1)?;
+
+        holidays.push(Holiday { name: name.trim().to_owned(), rule });
+    }
+
+    Ok(holidays)
+}
+
+fn parse_rule(s: &str) -> Option<Rule> {
+    if let Some(offset) = s.strip_prefix("easter") {
+        let offset = match offset {
+            "" => 0,
+            s  => s.strip_prefix('+').unwrap_or(s).parse().ok()?,
+        };
+
+        return Some(Rule::Easter(offset))
+    }
+
+    if s.contains('/') {
+        let mut parts = s.split('/');
+
+        let month: i8 = parts.next()?.parse().ok()?;
+        let weekday = cal::weekday_index(parts.next()?)?;
+        let nth: i8 = parts.next()?.parse().ok()?;
+
+        if parts.next().is_some() || !(1..=12).contains(&month) || nth == 0 || !(-5..=5).contains(&nth) {
+            return None
+        }
+
+        let weekday = Weekday::from_sunday_zero_offset(weekday as i8).ok()?;
+
+        return Some(Rule::Weekday { month, weekday, nth })
+    }
+
+    let (month, day) = s.split_once('-')?;
+    let (month, day) = (month.parse().ok()?, day.parse().ok()?);
+
+    // February 29 is valid, even though it's only observed in leap years
+    Date::new(2000, month, day).ok()?;
+
+    Some(Rule::Fixed { month, day })
+}
@@ -0,0 +1,336 @@
+use std::borrow::Cow;
+use std::path::Path;
+
+use jiff::civil::{Date, DateTime, Weekday};
+use jiff::tz::TimeZone;
+use jiff::{Timestamp, Zoned};
+
+use tokio::fs;
+
+use crate::error::{Error, IcsError};
+use crate::event::Event;
+use crate::recur::{Frequency, Rule};
+use crate::warnln;
+
+struct Property<'a> {
+    name:   &'a str,
+    params: Vec<(&'a str, &'a str)>,
+    value:  &'a str,
+}
+
+impl<'a> Property<'a> {
+    fn parse(line: &'a str) -> Option<Property<'a>> {
+        let mut quoted = false;
+
+        let colon = line.char_indices()
+            .find(|(_, c)| {
+                if *c == '"' { quoted = !quoted }
+                *c == ':' && !quoted
+            })?.0;
+
+        let (head, value) = (&line[..colon], &line[colon + 1..]);
+        let mut head = head.split(';');
+
+        let name = head.next()?;
+        let params = head
+            .filter_map(|p| p.split_once('='))
+            .map(|(k, v)| (k, v.trim_matches('"')))
+            .collect();
+
+        Some(Property { name, params, value })
+    }
+
+    fn param(&self, name: &str) -> Option<&'a str> {
+        self.params.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| *v)
+    }
+}
+
+pub async fn read(path: impl AsRef<Path>) -> Result<Vec<Event>, Error> {
+    let path = path.as_ref();
+
+    let s = fs::read_to_string(path).await
+        .map_err(|e| IcsError::Read { e, path: path.to_owned() })?;
+
+    let (events, skipped) = parse(&s)
+        .map_err(|(line, reason)| IcsError::Parse { line, reason, path: path.to_owned() })?;
+
+    for line in skipped {
+        warnln!("Skipping the event with the RRULE on line {line}, it uses parts that aren't supported ({})", path.display());
+    }
+
+    Ok(events)
+}
+
+// RFC 5545 3.1: long lines are folded by inserting a CRLF followed by a single whitespace
+fn unfold(s: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+
+    for (i, line) in s.lines().enumerate() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some((_, last))) => last.push_str(rest),
+            _ => lines.push((i + 1, line.to_owned())),
+        }
+    }
+
+    lines
+}
+
+// Line and reason of what makes a file malformed
+type Malformed = (usize, &'static str);
+
+// Also returns the lines of valid RRULEs that can't be followed, their events are left out
+fn parse(s: &str) -> Result<(Vec<Event>, Vec<usize>), Malformed> {
+    let mut events = Vec::new();
+    let mut current: Option<(usize, Event)> = None;
+    let mut start: Option<Zoned> = None;
+
+    let mut skipped = Vec::new();
+    let mut unsupported: Option<usize> = None;
+
+    // An edited occurrence is a VEVENT of its own with the UID of the recurring one and the
+    // start it replaces as RECURRENCE-ID
+    let mut uid: Option<String> = None;
+    let mut recurrence_id: Option<Timestamp> = None;
+    let mut recurring: Vec<(String, usize)> = Vec::new();
+    let mut overrides: Vec<(String, Timestamp)> = Vec::new();
+
+    // Components inside the VEVENT being read, like VALARM
+    let mut nested = 0;
+
+    for (n, line) in unfold(s) {
+        if line.trim().is_empty() { continue }
+
+        let property = Property::parse(&line).ok_or((n, "expected `NAME:VALUE`"))?;
+        let name = property.name.to_ascii_uppercase();
+
+        // Their properties aren't the event's, an alarm has a SUMMARY and a DESCRIPTION of its own
+        if current.is_some() && name == "BEGIN" && !property.value.eq_ignore_ascii_case("VEVENT") {
+            nested += 1;
+            continue
+        }
+
+        if nested > 0 {
+            if name == "END" { nested -= 1 }
+            continue
+        }
+
+        match (name.as_str(), &mut current) {
+            ("BEGIN", None) if property.value.eq_ignore_ascii_case("VEVENT") => {
+                current = Some((n, Event::default()));
+                start = None;
+                unsupported = None;
+                uid = None;
+                recurrence_id = None;
+            },
+            ("BEGIN", Some(_)) if property.value.eq_ignore_ascii_case("VEVENT") => {
+                return Err((n, "nested VEVENT"))
+            },
+            ("END", Some((begin, _))) if property.value.eq_ignore_ascii_case("VEVENT") => {
+                let begin = *begin;
+                let (_, mut event) = current.take().unwrap();
+
+                let start = start.take().ok_or((begin, "VEVENT without DTSTART"))?;
+                event.start = start.timestamp();
+                event.time_zone = start.time_zone().clone();
+
+                if let Some(uid) = uid.take() {
+                    match recurrence_id.take() {
+                        Some(replaced) => overrides.push((uid, replaced)),
+                        None if event.rule.is_some() && unsupported.is_none() => recurring.push((uid, events.len())),
+                        None => {},
+                    }
+                }
+
+                match unsupported {
+                    Some(line) => skipped.push(line),
+                    None       => events.push(event),
+                }
+            },
+            ("DTSTART", Some(_)) => {
+                start = Some(zoned(property.value, &property).ok_or((n, "invalid DTSTART"))?);
+            },
+            ("DTEND", Some((_, event))) => {
+                event.end = Some(timestamp(property.value, &property).ok_or((n, "invalid DTEND"))?);
+            },
+            ("RRULE", Some((_, event))) => {
+                match rule(property.value).map_err(|reason| (n, reason))? {
+                    Some(rule) => event.rule = Some(rule),
+                    None       => unsupported = Some(n),
+                }
+            },
+            ("UID", Some(_)) => {
+                uid = Some(property.value.to_owned());
+            },
+            ("RECURRENCE-ID", Some(_)) => {
+                recurrence_id = Some(timestamp(property.value, &property).ok_or((n, "invalid RECURRENCE-ID"))?);
+            },
+            ("EXDATE", Some((_, event))) => {
+                for value in property.value.split(',') {
+                    let exdate = timestamp(value, &property).ok_or((n, "invalid EXDATE"))?;
+                    event.exdates.push(exdate);
+                }
+            },
+            ("SUMMARY", Some((_, event))) => {
+                event.summary = unescape(property.value);
+            },
+            ("DESCRIPTION", Some((_, event))) => {
+                event.description = unescape(property.value);
+            },
+            ("CATEGORIES", Some((_, event))) => {
+                if let Some(class) = property.value.split(',').map(class).find(|c| !c.is_empty()) {
+                    event.class = Cow::Owned(class);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    if let Some((begin, _)) = current {
+        return Err((begin, "VEVENT is never closed"))
+    }
+
+    // The recurring event leaves out the occurrences that were edited, as if they were in EXDATE
+    for (uid, replaced) in overrides {
+        for (_, i) in recurring.iter().filter(|(id, _)| *id == uid) {
+            events[*i].exdates.push(replaced);
+        }
+    }
+
+    Ok((events, skipped))
+}
+
+fn timestamp(value: &str, property: &Property) -> Option<Timestamp> {
+    zoned(value, property).map(|z| z.timestamp())
+}
+
+// In the time zone of TZID, UTC or the local one for dates and floating times
+fn zoned(value: &str, property: &Property) -> Option<Zoned> {
+    if property.param("VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE")) {
+        let date = Date::strptime("%Y%m%d", value).ok()?;
+        return date.to_zoned(TimeZone::system()).ok()
+    }
+
+    datetime(value, property.param("TZID"))
+}
+
+fn datetime(value: &str, tzid: Option<&str>) -> Option<Zoned> {
+    if value.len() == 8 {
+        let date = Date::strptime("%Y%m%d", value).ok()?;
+        return date.to_zoned(TimeZone::system()).ok()
+    }
+
+    if let Some(value) = value.strip_suffix(['Z', 'z']) {
+        let datetime = DateTime::strptime("%Y%m%dT%H%M%S", value).ok()?;
+        return datetime.to_zoned(TimeZone::UTC).ok()
+    }
+
+    let datetime = DateTime::strptime("%Y%m%dT%H%M%S", value).ok()?;
+
+    let tz = tzid
+        .and_then(|id| TimeZone::get(id).ok())
+        .unwrap_or_else(TimeZone::system);
+
+    datetime.to_zoned(tz).ok()
+}
+
+// `None` for a valid rule with parts that would change which days match, like BYWEEKNO or BYHOUR
+fn rule(value: &str) -> Result<Option<Rule>, &'static str> {
+    const INVALID: &str = "invalid RRULE";
+
+    let mut frequency = None;
+    let mut rule = Rule::new(Frequency::Daily);
+    let mut supported = true;
+
+    for part in value.split(';').filter(|part| !part.is_empty()) {
+        let (key, value) = part.split_once('=').ok_or(INVALID)?;
+
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => frequency = Some(match value.to_ascii_uppercase().as_str() {
+                "DAILY"   => Frequency::Daily,
+                "WEEKLY"  => Frequency::Weekly,
+                "MONTHLY" => Frequency::Monthly,
+                "YEARLY"  => Frequency::Yearly,
+                "SECONDLY" | "MINUTELY" | "HOURLY" => {
+                    supported = false;
+                    Frequency::Daily
+                },
+                _ => return Err(INVALID),
+            }),
+            "INTERVAL"   => rule.interval = value.parse().map_err(|_| INVALID)?,
+            "COUNT"      => rule.count = Some(value.parse().map_err(|_| INVALID)?),
+            "UNTIL"      => rule.until = Some(datetime(value, None).ok_or(INVALID)?.timestamp()),
+            "BYDAY"      => rule.by_day = value.split(',').map(weekday).collect::<Option<_>>().ok_or(INVALID)?,
+            "BYMONTHDAY" => rule.by_month_day = value.split(',').map(|d| d.parse().ok()).collect::<Option<_>>().ok_or(INVALID)?,
+            "BYMONTH"    => rule.by_month = value.split(',').map(|m| m.parse().ok().filter(|m| (1..=12).contains(m))).collect::<Option<_>>().ok_or(INVALID)?,
+            "BYSETPOS"   => rule.by_set_pos = value.split(',').map(|p| p.parse().ok().filter(|p| *p != 0)).collect::<Option<_>>().ok_or(INVALID)?,
+            "WKST"       => rule.week_start = weekday(value).filter(|(nth, _)| nth.is_none()).ok_or(INVALID)?.1,
+            _ => supported = false,
+        }
+    }
+
+    rule.frequency = frequency.ok_or("RRULE without FREQ")?;
+
+    Ok(supported.then_some(rule))
+}
+
+// `MO`, `2TU`, `-1FR`
+fn weekday(s: &str) -> Option<(Option<i8>, Weekday)> {
+    let (nth, day) = s.split_at_checked(s.len().checked_sub(2)?)?;
+
+    let weekday = match day.to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Monday,
+        "TU" => Weekday::Tuesday,
+        "WE" => Weekday::Wednesday,
+        "TH" => Weekday::Thursday,
+        "FR" => Weekday::Friday,
+        "SA" => Weekday::Saturday,
+        "SU" => Weekday::Sunday,
+        _ => return None,
+    };
+
+    let nth = match nth.is_empty() {
+        true  => None,
+        false => Some(nth.trim_start_matches('+').parse().ok()?),
+    };
+
+    Some((nth, weekday))
+}
+
+// CATEGORIES become CSS classes, so keep them to something GTK will accept. The prefix keeps
+// a category like `Holiday` or `Today` apart from the classes the grids set themselves.
+fn class(category: &str) -> String {
+    let name: String = category.trim()
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+            true  => c.to_ascii_lowercase(),
+            false => '-',
+        })
+        .collect();
+
+    match name.is_empty() {
+        true  => name,
+        false => format!("category-{name}"),
+    }
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue
+        }
+
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(c)         => out.push(c),
+            None            => out.push('\\'),
+        }
+    }
+
+    out
+}
@@ -0,0 +1,100 @@
+use gtk::gdk::{Key, ModifierType};
+
+use crate::error::ConfigError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Left,
+    Right,
+    Up,
+    Down,
+    PrevMonth,
+    NextMonth,
+    PrevYear,
+    NextYear,
+    Today,
+    Select,
+    MonthView,
+    WeekView,
+    YearView,
+    AgendaView,
+    Quit,
+}
+
+impl TryFrom<&str> for Action {
+    type Error = ConfigError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "left"       => Ok(Action::Left),
+            "right"      => Ok(Action::Right),
+            "up"         => Ok(Action::Up),
+            "down"       => Ok(Action::Down),
+            "prev-month" => Ok(Action::PrevMonth),
+            "next-month" => Ok(Action::NextMonth),
+            "prev-year"  => Ok(Action::PrevYear),
+            "next-year"  => Ok(Action::NextYear),
+            "today"      => Ok(Action::Today),
+            "select"     => Ok(Action::Select),
+            "month-view" => Ok(Action::MonthView),
+            "week-view"  => Ok(Action::WeekView),
+            "year-view"  => Ok(Action::YearView),
+            "agenda-view" => Ok(Action::AgendaView),
+            "quit"       => Ok(Action::Quit),
+            _            => Err(ConfigError::Action(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Keymap(Vec<(Key, ModifierType, Action)>);
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use Action::*;
+
+        let bindings = [
+            ("Left", Left), ("Right", Right), ("Up", Up), ("Down", Down),
+            ("Page_Up", PrevMonth), ("Page_Down", NextMonth),
+            ("<Shift>Page_Up", PrevYear), ("<Shift>Page_Down", NextYear),
+            ("Home", Today),
+            ("Return", Select), ("space", Select),
+            ("m", MonthView), ("w", WeekView), ("y", YearView), ("a", AgendaView),
+            ("Escape", Quit),
+        ];
+
+        let bindings = bindings.into_iter()
+            .filter_map(|(accel, action)| {
+                let (key, modifiers) = gtk::accelerator_parse(accel)?;
+                Some((key, modifiers, action))
+            })
+            .collect();
+
+        Keymap(bindings)
+    }
+}
+
+impl Keymap {
+    /// Replaces every binding of `action` with a whitespace separated list of accelerators (e.g. `<Shift>Page_Up`).
+    pub fn bind(&mut self, action: &str, accels: &str) -> Result<(), ConfigError> {
+        let action = Action::try_from(action)?;
+
+        let keys = accels.split_whitespace()
+            .map(|accel| gtk::accelerator_parse(accel).ok_or_else(|| ConfigError::Key(accel.to_owned())))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.0.retain(|(_, _, a)| *a != action);
+        self.0.extend(keys.into_iter().map(|(key, modifiers)| (key, modifiers, action)));
+
+        Ok(())
+    }
+
+    pub fn action(&self, key: Key, modifiers: ModifierType) -> Option<Action> {
+        let key = key.to_lower();
+        let modifiers = modifiers & gtk::accelerator_get_default_mod_mask();
+
+        self.0.iter()
+            .find(|(k, m, _)| k.to_lower() == key && *m == modifiers)
+            .map(|(_, _, action)| *action)
+    }
+}
@@ -1,7 +1,10 @@
 use std::path::PathBuf;
 
+use gtk::prelude::ApplicationExt;
+
 use error::{Error, ConfigError};
 use anchor::Anchor;
+use keymap::Keymap;
 
 static APP_NAME:   &str = "caffi";
 static APP_ID:     &str = "elvy.caffi";
@@ -10,9 +13,13 @@ static APP_BINARY: &str = "caffi";
 #[derive(argh::FromArgs)]
 /// Calendar
 struct Args {
-    /// first day of the week: (sun)day, (mon)day, (tue)sday...
-    #[argh(option, short = '1', default = "String::from(\"sunday\")")]
-    first: String,
+    /// first day of the week: (sun)day, (mon)day, (tue)sday... (default: from locale)
+    #[argh(option, short = '1')]
+    first: Option<String>,
+
+    /// days of the weekend, can be repeated: (fri)day, (sat)urday... (default: from locale)
+    #[argh(option, long = "weekend")]
+    weekend: Vec<String>,
 
     /// screen anchor point: (t)op, (b)ottom, (l)eft, (r)ight
     #[argh(option, short = 'a', long = "anchor")]
@@ -27,10 +34,58 @@ struct Args {
     #[argh(switch, short = 'C', long = "accent")]
     accent: bool,
 
+    /// show ISO 8601 week numbers
+    #[argh(switch, short = 'w', long = "week-numbers")]
+    week_numbers: bool,
+
+    /// path to an iCalendar (.ics) file with events
+    #[argh(option, short = 'e', long = "events")]
+    events: Vec<PathBuf>,
+
+    /// holidays to mark, can be repeated: de, gb, us or a path to a file
+    #[argh(option, long = "holidays")]
+    holidays: Vec<String>,
+
+    /// secondary calendar shown under each day: hijri
+    #[argh(option, long = "calendar")]
+    calendar: Option<String>,
+
+    /// initial view: month, week, year, agenda
+    #[argh(option, long = "view", default = "String::from(\"month\")")]
+    view: String,
+
+    /// number of days listed in the agenda
+    #[argh(option, long = "agenda-days", default = "14")]
+    agenda_days: u16,
+
+    /// show the window of the running instance, or start a new one
+    #[argh(switch, long = "show")]
+    show: bool,
+
+    /// hide the window of the running instance
+    #[argh(switch, long = "hide")]
+    hide: bool,
+
+    /// show or hide the window of the running instance
+    #[argh(switch, long = "toggle")]
+    toggle: bool,
+
+    /// show a month or a day: YYYY-MM, YYYY-MM-DD
+    #[argh(option, long = "goto")]
+    goto: Option<String>,
+
+    /// add a note to a day and reload the running instance: "YYYY-MM-DD [HH:MM] text"
+    #[argh(option, long = "note")]
+    note: Option<String>,
+
     /// path to the userstyle
     #[argh(option, short = 'u', long = "userstyle")]
     userstyle: Option<PathBuf>,
 
+    /// override a CSS variable of the style, can be repeated: name=value
+    #[argh(option, long = "var")]
+    vars: Vec<String>,
+
     /// print version
     #[argh(switch, short = 'v', long = "version")]
     version: bool,
@@ -56,9 +111,88 @@ fn main() -> Result<(), Error> {
         anchors |= a?;
     }
 
+    if let Some(day) = args.first.as_deref().filter(|day| !day.is_empty()) {
+        cal::weekday_index(day).ok_or_else(|| error::CLIError::Weekday(day.to_owned()))?;
+    }
+
+    let view = app::View::try_from(args.view.as_str())?;
+
+    let calendar = match &args.calendar {
+        Some(name) => Some(calendar::system(name).ok_or_else(|| error::CLIError::Calendar(name.to_owned()))?),
+        None       => None,
+    };
+
+    let goto = match &args.goto {
+        Some(s) => Some(cal::parse_date(s).ok_or_else(|| error::CLIError::Date(s.to_owned()))?),
+        None    => None,
+    };
+
+    if let Some(s) = &args.note {
+        let (date, time, text) = note::parse_entry(s).ok_or_else(|| error::CLIError::Note(s.clone()))?;
+
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("a runtime for writing the note")
+            .block_on(note::write(date, time, text))?;
+    }
+
     warning(&args);
 
-    let app = relm4::RelmApp::new(crate::APP_ID).with_args(vec![]);
+    // Options are already parsed, the running instance only gets to know what to do with its window
+    let mut command = vec![String::from(crate::APP_BINARY)];
+
+    match (args.show, args.hide, args.toggle) {
+        (_, true, _) => command.push(String::from("hide")),
+        (_, _, true) => command.push(String::from("toggle")),
+        _            => command.push(String::from("show")),
+    }
+
+    if let Some(date) = goto {
+        command.extend([String::from("goto"), date.to_string()]);
+    }
+
+    if args.note.is_some() {
+        command.push(String::from("reload"));
+    }
+
+    let app = relm4::RelmApp::new(crate::APP_ID).with_args(command);
+
+    let application = relm4::main_application();
+    application.set_flags(gtk::gio::ApplicationFlags::HANDLES_COMMAND_LINE);
+    application.connect_command_line(app::App::command_line);
+
+    let (actions, receiver) = relm4::channel();
+    app::App::add_actions(&application, &actions);
+
+    let entries = config::read()?;
+
+    let weekend = match args.weekend.is_empty() {
+        true  => cal::locale_weekend(),
+        false => cal::weekend(&args.weekend)?,
+    };
+
+    let mut keymap = Keymap::default();
+
+    for entry in entries.iter().filter(|e| e.section == "keys") {
+        keymap.bind(&entry.key, &entry.value)?;
+    }
+
+    let mut vars = Vec::new();
+
+    for entry in entries.iter().filter(|e| e.section == "vars") {
+        let name = style::var_name(&entry.key).ok_or_else(|| ConfigError::Var(entry.key.clone()))?;
+        vars.push((name.to_owned(), entry.value.clone()));
+    }
+
+    // Applied in order, so the command line wins over the config file
+    for var in &args.vars {
+        let (name, value) = var.split_once('=')
+            .and_then(|(name, value)| Some((style::var_name(name)?, value.trim())))
+            .ok_or_else(|| error::CLIError::Var(var.clone()))?;
+
+        vars.push((name.to_owned(), value.to_owned()));
+    }
 
     app::WM_CONFIG.get_or_init(|| app::WMConfig {
         anchors,
@@ -66,8 +200,18 @@ fn main() -> Result<(), Error> {
     });
 
     app.run_async::<app::App>(app::Config {
-        first: args.first,
+        first: args.first.unwrap_or_default(),
+        weekend,
+        events: args.events,
+        keymap,
+        week_numbers: args.week_numbers,
+        calendar,
+        holidays: args.holidays,
+        view,
+        agenda_days: args.agenda_days,
         userstyle: args.userstyle,
+        vars,
+        actions: receiver,
 
         #[cfg(feature = "Accent")]
         accent: args.accent,
@@ -121,13 +265,24 @@ pub async fn config_dir() -> Result<PathBuf, ConfigError> {
 
 #[cfg(feature = "Accent")]
 mod accent;
+mod agenda;
 mod anchor;
 mod app;
 mod cal;
+mod calendar;
+mod clock;
+mod config;
+#[cfg(feature = "DBus")]
+mod dbus;
 mod error;
 mod event;
+mod holiday;
+mod ics;
+mod keymap;
 mod label;
+mod note;
 mod proto;
+mod recur;
 mod style;
 mod widgets;
 mod xdg;
@@ -1,9 +1,170 @@
+use std::path::{Path, PathBuf};
+
 use jiff::civil::{Date, Time};
 
-pub fn read(date: Date) -> Vec<String> {
-    todo!();
+use tokio::fs::{self, File};
+use tokio::io::AsyncWriteExt;
+
+use crate::error::{Error, NoteError};
+
+#[derive(Debug)]
+pub struct Note {
+    pub time: Option<Time>,
+    pub text: String,
+}
+
+impl Note {
+    fn parse(line: &str) -> Option<Note> {
+        let (time, text) = line.split_once('\t')?;
+
+        let time = match time.is_empty() {
+            true  => None,
+            false => Some(time.parse().ok()?),
+        };
+
+        Some(Note { time, text: unescape(text) })
+    }
+
+    fn format(&self) -> String {
+        match self.time {
+            Some(time) => format!("{}\t{}", time.strftime("%H:%M"), escape(&self.text)),
+            None       => format!("\t{}", escape(&self.text)),
+        }
+    }
+}
+
+pub fn dir() -> PathBuf {
+    let mut dir = crate::xdg::data_dir();
+    dir.push(crate::APP_BINARY);
+    dir.push("notes");
+    dir
+}
+
+fn path(date: Date) -> PathBuf {
+    let mut path = dir();
+    path.push(date.to_string());
+    path
+}
+
+pub async fn read(date: Date) -> Result<Vec<Note>, Error> {
+    let path = path(date);
+
+    let s = match fs::read_to_string(&path).await {
+        Ok(s) => s,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(NoteError::Read { e, path }.into()),
+    };
+
+    s.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| Note::parse(line).ok_or_else(|| NoteError::Parse { line: i + 1, path: path.clone() }))
+        .collect::<Result<_, _>>()
+        .map_err(Into::into)
+}
+
+/// Takes `YYYY-MM-DD [HH:MM] text` of `--note`.
+pub fn parse_entry(s: &str) -> Option<(Date, Option<Time>, &str)> {
+    let (date, rest) = s.trim().split_once(' ')?;
+    let date = date.parse::<Date>().ok().filter(|date| crate::cal::is_supported(date.year()))?;
+
+    let rest = rest.trim_start();
+    let time = rest.split_once(' ')
+        .and_then(|(time, text)| Some((Time::strptime("%H:%M", time).ok()?, text)));
+
+    let (time, text) = match time {
+        Some((time, text)) => (Some(time), text),
+        None => (None, rest),
+    };
+
+    let text = text.trim();
+
+    (!text.is_empty()).then_some((date, time, text))
+}
+
+pub async fn write(date: Date, time: Option<Time>, text: &str) -> Result<(), Error> {
+    let mut notes = read(date).await?;
+
+    notes.push(Note { time, text: text.to_owned() });
+    notes.sort_by_key(|note| note.time);
+
+    let s = notes.iter()
+        .map(Note::format)
+        .fold(String::new(), |s, line| s + &line + "\n");
+
+    let dir = dir();
+
+    fs::create_dir_all(&dir).await
+        .map_err(|e| NoteError::Create { e, path: dir })?;
+
+    replace(path(date), s.as_bytes()).await.map_err(Into::into)
+}
+
+/// Dates within `from..=to` that have at least one note.
+pub async fn dates(from: Date, to: Date) -> Result<Vec<Date>, Error> {
+    let dir = dir();
+
+    let mut entries = match fs::read_dir(&dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(NoteError::Read { e, path: dir }.into()),
+    };
+
+    let mut dates = Vec::new();
+
+    while let Some(entry) = entries.next_entry().await.map_err(|e| NoteError::Read { e, path: dir.clone() })? {
+        let Some(date) = entry.file_name().to_str().and_then(|s| s.parse::<Date>().ok()) else { continue };
+
+        if (from..=to).contains(&date) {
+            dates.push(date);
+        }
+    }
+
+    Ok(dates)
 }
 
-pub fn write(date: Date, time: Option<Time>) -> Result<(), std::io::Error> {
-    todo!();
+// Write to a temporary file first and rename it over the original,
+// so a crash never leaves a half written note behind.
+async fn replace(path: impl AsRef<Path>, bytes: &[u8]) -> Result<(), NoteError> {
+    let path = path.as_ref();
+
+    let mut tmp = path.to_owned();
+    tmp.set_extension("tmp");
+
+    let mut f = File::create(&tmp).await
+        .map_err(|e| NoteError::Create { e, path: tmp.clone() })?;
+
+    f.write_all(bytes).await
+        .map_err(|e| NoteError::Write { e, path: tmp.clone() })?;
+
+    f.sync_all().await
+        .map_err(|e| NoteError::Write { e, path: tmp.clone() })?;
+
+    fs::rename(&tmp, path).await
+        .map_err(|e| NoteError::Write { e, path: path.to_owned() })
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\n', "\\n").replace('\t', "\\t")
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue
+        }
+
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(c)   => out.push(c),
+            None      => out.push('\\'),
+        }
+    }
+
+    out
 }
@@ -0,0 +1,208 @@
+use jiff::civil::{Date, Weekday};
+use jiff::{Timestamp, ToSpan, Zoned};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A subset of RFC 5545 RRULE: FREQ, INTERVAL, BYDAY, BYMONTHDAY, BYMONTH, BYSETPOS, WKST, COUNT and UNTIL.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub frequency:    Frequency,
+    pub interval:     u16,
+    pub by_day:       Vec<(Option<i8>, Weekday)>,
+    pub by_month_day: Vec<i8>,
+    pub by_month:     Vec<i8>,
+    pub by_set_pos:   Vec<i16>,
+    pub week_start:   Weekday,
+    pub count:        Option<u32>,
+    pub until:        Option<Timestamp>,
+}
+
+impl Rule {
+    pub fn new(frequency: Frequency) -> Self {
+        Self {
+            frequency,
+            interval:     1,
+            by_day:       Vec::new(),
+            by_month_day: Vec::new(),
+            by_month:     Vec::new(),
+            by_set_pos:   Vec::new(),
+            week_start:   Weekday::Monday,
+            count:        None,
+            until:        None,
+        }
+    }
+
+    /// Every occurrence starting at `start` up to and including the `to` date, both in the time zone of `start`.
+    pub fn expand(&self, start: &Zoned, to: Date) -> Vec<Zoned> {
+        let first = start.date();
+        let interval = self.interval.max(1) as i64;
+
+        let mut occurrences = Vec::new();
+        let mut count = 0;
+
+        for n in 0.. {
+            let Some(period) = self.period(first, n * interval) else { break };
+
+            if period > to { break }
+
+            for day in self.candidates(first, period) {
+                if day < first { continue }
+                if day > to { return occurrences }
+
+                let Ok(zoned) = day.to_datetime(start.time()).to_zoned(start.time_zone().clone()) else { continue };
+
+                if self.until.is_some_and(|until| zoned.timestamp() > until) { return occurrences }
+                if self.count.is_some_and(|max| count >= max) { return occurrences }
+
+                count += 1;
+                occurrences.push(zoned);
+            }
+        }
+
+        occurrences
+    }
+
+    // First day of the n-th period since `first`
+    fn period(&self, first: Date, n: i64) -> Option<Date> {
+        match self.frequency {
+            Frequency::Daily   => first.checked_add(n.days()).ok(),
+            Frequency::Weekly  => {
+                let week_start = first.checked_sub((first.weekday().since(self.week_start) as i64).days()).ok()?;
+                week_start.checked_add(n.weeks()).ok()
+            },
+            Frequency::Monthly => first.first_of_month().checked_add(n.months()).ok(),
+            Frequency::Yearly  => Date::new(first.year(), 1, 1).ok()?.checked_add(n.years()).ok(),
+        }
+    }
+
+    fn candidates(&self, first: Date, period: Date) -> Vec<Date> {
+        let mut days = match self.frequency {
+            Frequency::Daily => {
+                let matches_day = self.by_day.is_empty() || self.by_day.iter().any(|(_, wd)| *wd == period.weekday());
+                let matches_month_day = self.by_month_day.is_empty() || self.month_days(period).any(|d| d == period.day());
+
+                match matches_day && matches_month_day {
+                    true  => vec![period],
+                    false => Vec::new(),
+                }
+            },
+            Frequency::Weekly => {
+                let weekdays: Vec<Weekday> = match self.by_day.is_empty() {
+                    true  => vec![first.weekday()],
+                    false => self.by_day.iter().map(|(_, wd)| *wd).collect(),
+                };
+
+                weekdays.into_iter()
+                    .filter_map(|wd| period.checked_add((wd.since(self.week_start) as i64).days()).ok())
+                    .collect()
+            },
+            Frequency::Monthly => self.in_month(first, period),
+            Frequency::Yearly => {
+                let year = period.year();
+
+                match (self.by_month.is_empty(), self.by_day.is_empty(), self.by_month_day.is_empty()) {
+                    (false, _, _) => self.by_month.iter()
+                        .filter_map(|&month| Date::new(year, month, 1).ok())
+                        .flat_map(|month| self.in_month(first, month))
+                        .collect(),
+                    // BYDAY counts weekdays of the whole year without BYMONTH
+                    (true, false, true) => self.in_year(year),
+                    (true, _, false) => (1..=12)
+                        .filter_map(|month| Date::new(year, month, 1).ok())
+                        .flat_map(|month| self.in_month(first, month))
+                        .collect(),
+                    (true, true, true) => Date::new(year, first.month(), first.day()).ok().into_iter().collect(),
+                }
+            },
+        };
+
+        if !self.by_month.is_empty() {
+            days.retain(|d| self.by_month.contains(&d.month()));
+        }
+
+        days.sort();
+        days.dedup();
+
+        match self.by_set_pos.is_empty() {
+            true  => days,
+            false => self.set_positions(&days),
+        }
+    }
+
+    // Days of the month starting at `month` picked by BYDAY and BYMONTHDAY, the day of `first` without them
+    fn in_month(&self, first: Date, month: Date) -> Vec<Date> {
+        let by_month_day: Vec<Date> = self.month_days(month)
+            .filter_map(|d| month.with().day(d).build().ok())
+            .collect();
+
+        let by_day: Vec<Date> = self.by_day.iter()
+            .flat_map(|(nth, wd)| match nth {
+                Some(nth) => month.nth_weekday_of_month(*nth, *wd).ok().into_iter().collect(),
+                None      => (1..=5).filter_map(|nth| month.nth_weekday_of_month(nth, *wd).ok()).collect::<Vec<_>>(),
+            })
+            .collect();
+
+        match (self.by_month_day.is_empty(), self.by_day.is_empty()) {
+            (true,  true)  => month.with().day(first.day()).build().ok().into_iter().collect(),
+            (false, true)  => by_month_day,
+            (true,  false) => by_day,
+            (false, false) => by_day.into_iter().filter(|d| by_month_day.contains(d)).collect(),
+        }
+    }
+
+    // BYDAY counted over the whole year, `20MO` is the 20th Monday of it
+    fn in_year(&self, year: i16) -> Vec<Date> {
+        let (Ok(start), Ok(end)) = (Date::new(year, 1, 1), Date::new(year, 12, 31)) else { return Vec::new() };
+
+        self.by_day.iter()
+            .flat_map(|&(nth, wd)| {
+                // `nth_weekday` doesn't count the day it starts from
+                let from_start = start.yesterday().ok();
+                let from_end = end.tomorrow().ok();
+
+                match nth {
+                    Some(nth) if nth > 0 => from_start.and_then(|d| d.nth_weekday(nth as i32, wd).ok()).into_iter().collect(),
+                    Some(nth) => from_end.and_then(|d| d.nth_weekday(nth as i32, wd).ok()).into_iter().collect(),
+                    None => {
+                        let first = start.checked_add((wd.since(start.weekday()) as i64).days()).ok();
+
+                        std::iter::successors(first, |d| d.checked_add(1.week()).ok())
+                            .take_while(|d| *d <= end)
+                            .collect::<Vec<_>>()
+                    },
+                }
+            })
+            .filter(|d| d.year() == year)
+            .collect()
+    }
+
+    // BYSETPOS picks from the days of a period, negative positions count from the end
+    fn set_positions(&self, days: &[Date]) -> Vec<Date> {
+        let len = days.len() as i16;
+
+        let mut picked: Vec<Date> = self.by_set_pos.iter()
+            .map(|&pos| if pos < 0 { len + pos } else { pos - 1 })
+            .filter(|i| (0..len).contains(i))
+            .map(|i| days[i as usize])
+            .collect();
+
+        picked.sort();
+        picked.dedup();
+        picked
+    }
+
+    // BYMONTHDAY resolved against the month of `date`, negative values count from the end
+    fn month_days(&self, date: Date) -> impl Iterator<Item = i8> + '_ {
+        let days = date.days_in_month();
+
+        self.by_month_day.iter()
+            .map(move |&d| if d < 0 { days + 1 + d } else { d })
+            .filter(move |d| (1..=days).contains(d))
+    }
+}
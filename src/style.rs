@@ -6,11 +6,15 @@ use tokio::fs::{self, File};
 use tokio::io::AsyncWriteExt;
 
 use crate::error::{CacheError, Error, StyleError};
+use crate::warnln;
 
-#[derive(Default, Copy, Clone)]
+#[derive(Default, Clone)]
 pub struct StyleSettings {
     #[cfg(feature = "Accent")]
     pub accent: bool,
+
+    /// Custom properties to override, by name without the leading `--`.
+    pub vars: Vec<(String, String)>,
 }
 
 #[allow(unused_variables)]
@@ -53,6 +57,24 @@ pub async fn find(path: impl Into<PathBuf>, settings: StyleSettings) -> Result<C
     s
 }
 
+/// Path of the style `find` reads from `path`.
+pub fn locate(path: impl Into<PathBuf>) -> PathBuf {
+    let mut path = path.into();
+
+    path.push("style");
+
+    for ext in ["scss", "sass"] {
+        path.set_extension(ext);
+
+        if path.exists() {
+            return path
+        }
+    }
+
+    path.set_extension("css");
+    path
+}
+
 #[allow(unused_variables)]
 pub async fn default(settings: StyleSettings) -> Cow<'static, str> {
     static DEFAULT_STYLE: &str = include_str!(concat!(env!("OUT_DIR"), "/default.css"));
@@ -105,39 +127,118 @@ pub async fn read(path: impl AsRef<Path>) -> Result<Cow<'static, str>, Error> {
     }
 }
 
-async fn compile_sass(style_path: impl AsRef<std::path::Path>) -> Result<String, Error> {
-    use crate::{xdg, error};
+/// The style and every Sass file it loads through `@use`, `@forward` or `@import`, recursively.
+pub async fn dependencies(path: impl AsRef<Path>) -> Vec<PathBuf> {
+    let mut paths = vec![path.as_ref().to_owned()];
+    let mut i = 0;
+
+    while i < paths.len() {
+        let path = paths[i].clone();
+        i += 1;
+
+        if !matches!(path.extension().and_then(OsStr::to_str), Some("sass" | "scss")) {
+            continue
+        }
+
+        let Ok(s) = fs::read_to_string(&path).await else { continue };
+        let dir = path.parent().unwrap_or(Path::new("."));
+
+        for url in imports(&s) {
+            if let Some(dependency) = resolve_import(dir, url).await {
+                if !paths.contains(&dependency) {
+                    paths.push(dependency);
+                }
+            }
+        }
+    }
+
+    paths
+}
 
+// Urls of the rules loading other files, built-in modules and plain CSS imports excluded
+fn imports(s: &str) -> Vec<&str> {
+    // Quoted string at the start of `s` and what follows it
+    fn quoted(s: &str) -> Option<(&str, &str)> {
+        let s = s.trim_start();
+        let quote = s.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+        let (url, rest) = s[1..].split_once(quote)?;
+
+        Some((url, rest))
+    }
+
+    let mut urls = Vec::new();
+
+    for line in s.lines().map(str::trim_start) {
+        if let Some(rest) = line.strip_prefix("@use").or_else(|| line.strip_prefix("@forward")) {
+            urls.extend(quoted(rest).map(|(url, _)| url));
+        }
+        else if let Some(mut rest) = line.strip_prefix("@import") {
+            // `@import "a", "b";`
+            while let Some((url, tail)) = quoted(rest) {
+                urls.push(url);
+                rest = tail.trim_start().strip_prefix(',').unwrap_or("");
+            }
+        }
+    }
+
+    urls.retain(|url| !url.starts_with("sass:") && !url.contains("://") && !url.ends_with(".css"));
+    urls
+}
+
+// Finds the file an import refers to, trying partials (`_name.scss`) and index files like Sass does
+async fn resolve_import(dir: &Path, url: &str) -> Option<PathBuf> {
+    let path = dir.join(url);
+    let name = path.file_name()?.to_str()?.to_owned();
+
+    let names = match path.extension().and_then(OsStr::to_str) {
+        Some("sass" | "scss") => vec![name.clone(), format!("_{name}")],
+        _ => ["scss", "sass"].iter()
+            .flat_map(|ext| [format!("{name}.{ext}"), format!("_{name}.{ext}"), format!("{name}/_index.{ext}"), format!("{name}/index.{ext}")])
+            .collect(),
+    };
+
+    for name in names {
+        let candidate = path.with_file_name(name);
+
+        // Canonical, so a file reached through `..` is only watched once
+        if let Ok(path) = fs::canonicalize(&candidate).await {
+            return Some(path)
+        }
+    }
+
+    None
+}
+
+async fn compile_sass(style_path: impl AsRef<std::path::Path>) -> Result<String, Error> {
     let style_path = style_path.as_ref();
 
-    let style_meta = match fs::metadata(style_path).await {
-        Ok(m) => m,
+    let style = match fs::read_to_string(style_path).await {
+        Ok(s) => s,
         Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Err(StyleError::NotFound(e).into()),
-        Err(e) => {
-            return Err(StyleError::Meta { e, path: style_path.to_owned() }.into())
-        }
+        Err(e) => return Err(StyleError::Read { e, path: style_path.to_owned() }.into()),
     };
 
-    let style_mtime = style_meta.modified().map_err(|e| StyleError::MTime { e, path: style_path.to_owned() })?;
+    let canonical = fs::canonicalize(style_path).await.unwrap_or_else(|_| style_path.to_owned());
+    let entry = CacheEntry::new(&canonical, &style).await;
 
-    let mut cache_path = xdg::cache_dir();
-    cache_path.push(crate::APP_BINARY);
-    cache_path.set_extension("css");
+    match fs::read_to_string(entry.path()).await {
+        Ok(compiled) => {
+            if let Err(e) = entry.touch().await {
+                eprintln!("{e}");
+            }
 
-    if let Ok(cache_meta) = fs::metadata(&cache_path).await {
-        if Some(style_mtime) == cache_meta.modified().ok() {
-            return fs::read_to_string(&cache_path).await
-                .map_err(|e| error::CacheError::Read { e, path: cache_path })
-                .map_err(Into::into);
-        }
+            return Ok(compiled)
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+        Err(e) => eprintln!("{}", CacheError::Read { e, path: entry.path() }),
     }
 
     #[cfg(feature = "Sass")]
     let compiled = {
-        let style = fs::read_to_string(style_path).await
-                .map_err(|e| StyleError::Read { e, path: style_path.to_owned() })?;
+        let options = grass::Options::default()
+            .load_path(style_path.parent().unwrap_or(Path::new(".")));
 
-        grass::from_string(style, &grass::Options::default()).map_err(StyleError::Sass)?
+        grass::from_string(style, &options).map_err(StyleError::Sass)?
     };
 
     #[cfg(not(feature = "Sass"))]
@@ -159,26 +260,123 @@ async fn compile_sass(style_path: impl AsRef<std::path::Path>) -> Result<String,
         unsafe { String::from_utf8_unchecked(output.stdout) }
     };
 
-    if let Err(e) = cache(cache_path, &compiled, style_mtime).await {
+    if let Err(e) = entry.write(&compiled).await {
         eprintln!("{e}");
     }
 
+    entry.clean().await;
+
     Ok(compiled)
 }
 
-async fn cache(path: impl AsRef<Path>, style: &str, time: std::time::SystemTime) -> Result<(), CacheError> {
-    use crate::error::CacheError;
+/// A compiled style in `$XDG_CACHE_HOME/caffi/`, named `<path hash>-<content hash>.css`.
+///
+/// The content hash covers the source and every file it imports, so editing a partial or
+/// switching between userstyles never picks up a wrong entry.
+struct CacheEntry {
+    dir: PathBuf,
+    path_hash: u64,
+    content_hash: u64,
+}
 
-    let path = path.as_ref();
+impl CacheEntry {
+    // Entries of styles that weren't compiled for this long are removed
+    const MAX_AGE: std::time::Duration = std::time::Duration::from_secs(30 * 24 * 60 * 60);
+
+    async fn new(path: &Path, source: &str) -> Self {
+        let mut hasher = Fnv1a::new();
+        hasher.field(path.as_os_str().as_encoded_bytes());
 
-    let mut f = File::create(path).await
-        .map_err(|e| CacheError::Create { e, path: path.to_owned() })?;
+        let path_hash = hasher.0;
 
-    f.write_all(style.as_bytes()).await
-        .map_err(|e| CacheError::Write { e, path: path.to_owned() })?;
+        hasher.field(source.as_bytes());
+
+        for dependency in dependencies(path).await.iter().skip(1) {
+            hasher.field(dependency.as_os_str().as_encoded_bytes());
+            hasher.field(&fs::read(dependency).await.unwrap_or_default());
+        }
+
+        let mut dir = crate::xdg::cache_dir();
+        dir.push(crate::APP_BINARY);
+
+        CacheEntry { dir, path_hash, content_hash: hasher.0 }
+    }
+
+    fn path(&self) -> PathBuf {
+        self.dir.join(format!("{:016x}-{:016x}.css", self.path_hash, self.content_hash))
+    }
+
+    // Marks the entry as used, so that `clean` keeps it
+    async fn touch(&self) -> Result<(), CacheError> {
+        let path = self.path();
+
+        let f = File::options().append(true).open(&path).await
+            .map_err(|e| CacheError::Touch { e, path: path.clone() })?;
+
+        f.into_std().await.set_modified(std::time::SystemTime::now())
+            .map_err(|e| CacheError::Touch { e, path })
+    }
 
-    f.into_std().await.set_modified(time)
-        .map_err(|e| CacheError::MTime { e, path: path.to_owned() })
+    async fn write(&self, style: &str) -> Result<(), CacheError> {
+        let path = self.path();
+
+        fs::create_dir_all(&self.dir).await
+            .map_err(|e| CacheError::Dir { e, path: self.dir.clone() })?;
+
+        let mut f = File::create(&path).await
+            .map_err(|e| CacheError::Create { e, path: path.clone() })?;
+
+        f.write_all(style.as_bytes()).await
+            .map_err(|e| CacheError::Write { e, path })
+    }
+
+    // Removes the previous versions of this style, entries left unused by other styles
+    // and the single file cache of older versions
+    async fn clean(&self) {
+        let _ = fs::remove_file(self.dir.with_extension("css")).await;
+
+        let Ok(mut entries) = fs::read_dir(&self.dir).await else { return };
+
+        let current = self.path();
+        let prefix = format!("{:016x}-", self.path_hash);
+
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+
+            if path == current || path.extension() != Some(OsStr::new("css")) {
+                continue
+            }
+
+            let stale = entry.file_name().to_string_lossy().starts_with(&prefix) || entry.metadata().await
+                .and_then(|m| m.modified())
+                .is_ok_and(|mtime| mtime.elapsed().is_ok_and(|age| age > Self::MAX_AGE));
+
+            if stale {
+                let _ = fs::remove_file(path).await;
+            }
+        }
+    }
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` names cache entries the same across Rust releases.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(0xcbf29ce484222325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+
+    // Length-prefixed, so that the boundary between two fields is part of the hash
+    fn field(&mut self, bytes: &[u8]) {
+        self.write(&(bytes.len() as u64).to_le_bytes());
+        self.write(bytes);
+    }
 }
 
 #[cfg(feature = "Accent")]
@@ -200,7 +398,29 @@ async fn apply_accent(s: Cow<'static, str>) -> Result<Cow<'static, str>, Error>
     }
 }
 
-#[cfg(feature = "Accent")]
+/// Checks a custom property given as `name` or `--name`, returning its name without the dashes.
+pub fn var_name(name: &str) -> Option<&str> {
+    let name = name.trim();
+    let name = name.strip_prefix("--").unwrap_or(name);
+
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+
+    valid.then_some(name)
+}
+
+/// Overrides every declaration of each variable, warning about the ones the style doesn't declare.
+pub fn set_vars(s: Cow<'static, str>, vars: &[(String, String)]) -> Cow<'static, str> {
+    vars.iter().fold(s, |s, (name, value)| {
+        match set_var(s.as_ref(), name, value) {
+            Some(s) => Cow::Owned(s),
+            None => {
+                warnln!("'--{name}' isn't declared in the style, so it can't be overridden");
+                s
+            }
+        }
+    })
+}
+
 fn find_var(s: &str, name: &str) -> Option<std::ops::Range<usize>>  {
     let start = s.find(&format!("--{name}:"))?;
     let end = s[start..].find(';')?;
@@ -208,14 +428,24 @@ fn find_var(s: &str, name: &str) -> Option<std::ops::Range<usize>>  {
     Some(start..start + end)
 }
 
-#[cfg(feature = "Accent")]
+// Replaces the value of every declaration, none if there's none
 fn set_var(s: impl Into<String>, name: &str, value: &str) -> Option<String> {
     let mut s = s.into();
-    s.replace_range(
-        find_var(&s, name)?,
-        &format!("--{name}: {value}"));
+    let declaration = format!("--{name}: {value}");
+
+    let mut from = 0;
+    let mut found = false;
+
+    while let Some(range) = find_var(&s[from..], name) {
+        let range = from + range.start..from + range.end;
+
+        from = range.start + declaration.len();
+        found = true;
+
+        s.replace_range(range, &declaration);
+    }
 
-    Some(s)
+    found.then_some(s)
 }
 
 #[cfg(feature = "Accent")]
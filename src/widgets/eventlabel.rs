@@ -0,0 +1,33 @@
+use gtk::Align;
+use gtk::prelude::WidgetExt;
+
+use jiff::Zoned;
+use jiff::civil::Date;
+
+use crate::event::Event;
+
+/// Labels of the occurrences on `date`, as the details and the agenda list them.
+pub fn for_day(events: &[Event], date: Date) -> Vec<gtk::Label> {
+    let mut spans: Vec<(&Event, Zoned, Zoned)> = events.iter()
+        .flat_map(|event| event.spans(date, date).into_iter().map(move |(start, end)| (event, start, end)))
+        .collect();
+
+    // Occurrences that started on an earlier day come first, like all-day events
+    spans.sort_by_key(|(_, start, _)| (start.date() == date).then(|| start.time()));
+
+    spans.into_iter()
+        .map(|(event, start, end)| {
+            let text = match event.time_range_on(&start, &end, date) {
+                Some(time) => format!("{time} {}", event.summary),
+                None       => event.summary.clone(),
+            };
+
+            let label = gtk::Label::new(Some(&text));
+            label.add_css_class("event");
+            label.add_css_class(&event.class);
+            label.set_halign(Align::Start);
+            label.set_wrap(true);
+            label
+        })
+        .collect()
+}
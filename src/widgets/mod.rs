@@ -1,3 +1,5 @@
 pub mod monthgrid;
 pub mod yeargrid;
+pub mod weekgrid;
 pub mod anilabel;
+pub mod eventlabel;
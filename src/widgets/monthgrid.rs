@@ -1,19 +1,23 @@
-use glib::object::Cast;
+use glib::object::{Cast, IsA};
 use gtk::graphene::Point;
 use gtk::glib::{self, Object};
+use gtk::PickFlags;
 use gtk::prelude::{GridExt,WidgetExt, WidgetExtManual};
+use gtk::subclass::prelude::ObjectSubclassIsExt;
 
-use jiff::tz::TimeZone;
 use jiff::ToSpan;
-use jiff::civil::Date;
+use jiff::civil::{Date, Weekday};
 
 use crate::cal::{self, CalendarDay};
+use crate::calendar;
+use crate::event::Event;
+use crate::holiday::{self, Holiday};
 
-pub const ROWS: u8 = 6;
+pub const ROWS: u8 = crate::cal::WEEKS;
 pub const COLUMNS: u8 = crate::cal::WEEKDAYS.len() as u8;
 
 mod imp {
-    use std::cell::{Cell};
+    use std::cell::{Cell, RefCell};
 
     use glib::Properties;
     use glib::subclass::types::ObjectSubclass;
@@ -27,7 +31,8 @@ mod imp {
     use gtk::subclass::prelude::DerivedObjectProperties;
     use gtk::Snapshot;
 
-    use super::{COLUMNS};
+    use crate::event::Event;
+    use crate::holiday::Holiday;
 
     #[derive(Properties, Default)]
     #[properties(wrapper_type = super::MonthGrid)]
@@ -45,10 +50,20 @@ mod imp {
         max_weekday_chars: Cell<u8>,
 
         #[property(get, set)]
-        translate: Cell<Point>,
+        week_numbers: Cell<bool>,
+
+        #[property(get, set)]
+        weekend: Cell<u8>,
 
         #[property(get, set)]
-        month_range: Cell<Range>
+        calendar: std::cell::RefCell<String>,
+
+        #[property(get, set)]
+        translate: Cell<Point>,
+
+        // Kept for tooltips, which are only built when one is about to be shown
+        pub events: RefCell<Vec<Event>>,
+        pub holidays: RefCell<Vec<Holiday>>,
     }
 
     impl WidgetImpl for MonthGrid {
@@ -70,62 +85,6 @@ mod imp {
     impl ObjectImpl for MonthGrid {}
     impl OrientableImpl for MonthGrid {}
     impl GridImpl for MonthGrid {}
-
-    // TODO: rename
-    #[derive(Copy, Clone, Default, Debug, PartialEq)]
-    pub struct GridCell { pub column: u8, pub row: u8 }
-
-    impl GridCell {
-        pub fn next(&self) -> GridCell {
-            let column = (self.column + 1) % COLUMNS;
-            let row = self.row + (self.column + 1) / COLUMNS;
-
-            GridCell { column, row }
-        }
-    }
-
-    impl From<u32> for GridCell {
-        fn from(n: u32) -> Self {
-            GridCell { column: (n >> 8) as u8, row: n as u8 }
-        }
-    }
-
-    // impl<'a> From<&'a Pos> for u32 {
-        // fn from(limits: &'a Pos) -> Self {
-            // (limits.column as u32) << 8 | limits.row as u32
-        // }
-    // }
-
-    impl From<GridCell> for u32 {
-        fn from(pos: GridCell) -> Self {
-            (pos.column as u32) << 8 | pos.row as u32
-        }
-    }
-
-    #[derive(glib::ValueDelegate, Copy, Clone, Default)]
-    #[value_delegate(from = u32)]
-    pub struct Range {
-        pub start: GridCell,
-        pub end:   GridCell,
-    }
-
-    impl From<u32> for Range {
-        fn from(n: u32) -> Self {
-            Range { start: GridCell::from(n >> 16), end: GridCell::from(n) }
-        }
-    }
-
-    impl<'a> From<&'a Range> for u32 {
-        fn from(range: &'a Range) -> Self {
-            u32::from(range.start) << 16 | u32::from(range.end)
-        }
-    }
-
-    impl From<Range> for u32 {
-        fn from(range: Range) -> Self {
-            u32::from(range.start) << 16 | u32::from(range.end)
-        }
-    }
 }
 
 glib::wrapper! {
@@ -142,6 +101,16 @@ impl Default for MonthGrid {
         grid.set_row_homogeneous(true);
         grid.connect_translate_notify(|grid| grid.queue_draw());
 
+        grid.set_has_tooltip(true);
+        grid.connect_query_tooltip(|grid, x, y, _, tooltip| {
+            let Some(markup) = grid.date_at(x as f64, y as f64).and_then(|date| grid.tooltip_markup(date)) else {
+                return false
+            };
+
+            tooltip.set_markup(Some(&markup));
+            true
+        });
+
         // TODO: Shader
         grid.connect_realize(|grid| {
             let child = std::cell::Cell::new(grid.first_child());
@@ -163,59 +132,180 @@ impl Default for MonthGrid {
 }
 
 impl MonthGrid {
+    // Columns taken before the first day of the week
     #[inline]
-    fn date(&self) -> Date {
-        Date::new(self.year() as _, self.month() as _, 1).unwrap()
+    fn offset(&self) -> i32 {
+        self.week_numbers() as i32
     }
 
     #[inline]
-    fn shows_date(current: Date, date: Date) -> bool {
-        date.year() == current.year() || date.year() == (current + 1.month()).year() || date.year() == (current - 1.month()).year()
+    fn page(&self) -> cal::Page {
+        cal::Page::new(self.year() as _, self.month() as _, cal::first_day(&self.first()))
+    }
+
+    /// First and last date shown in the grid, including days of adjacent months.
+    pub fn visible_range(&self) -> (Date, Date) {
+        self.page().range()
     }
 
     pub fn child_by_date(&self, date: impl Into<Date>) -> Option<gtk::Widget> {
-        let date = date.into();
+        let (column, row) = self.page().position(date.into())?;
 
-        let current = self.date();
+        self.child_at(column as i32 + self.offset(), row as i32 + 1)
+    }
+
+    pub fn date_by_child(&self, child: &impl IsA<gtk::Widget>) -> Option<Date> {
+        let (column, row, _, _) = self.query_child(child);
+        let column = column - self.offset();
 
-        if !MonthGrid::shows_date(current, date) {
+        if !(1..=ROWS as i32).contains(&row) || !(0..COLUMNS as i32).contains(&column) {
             return None
         }
 
-        let range = self.month_range();
-        let mut column = -1;
-        let mut row = -1;
+        self.page().date(column as u8, row as u8 - 1)
+    }
 
-        if date.month() == current.month() {
-            let diff = ((range.start.row - 1) * COLUMNS + range.start.column + date.day() as u8 - 1) as i8;
+    /// Date of the day cell under a point in the grid's coordinates.
+    pub fn date_at(&self, x: f64, y: f64) -> Option<Date> {
+        let mut child = self.pick(x, y, PickFlags::DEFAULT)?;
 
-            row = diff / COLUMNS as i8;
-            column = diff % COLUMNS as i8;
+        while child.parent().as_ref() != Some(self.upcast_ref()) {
+            child = child.parent()?;
         }
 
-        // if date < current.first_of_month() {
-            // let days = cal::days_in_month(date.year() as _, date.month() as _);
+        self.date_by_child(&child)
+    }
+
+    pub fn set_selected(&self, date: Option<Date>) {
+        self.mark_single("selected", date);
+    }
+
+    pub fn set_focused(&self, date: Option<Date>) {
+        self.mark_single("focused", date);
+    }
+
+    pub fn set_today(&self, date: Date) {
+        self.mark_single("today", Some(date));
+    }
 
-            // let zero = days - (COLUMNS * (range.start.row - 1)) - range.start.column;
-            // let diff = date.day() - zero as i8;
+    // Moves `class` to the day of `date`, removing it from every other cell
+    fn mark_single(&self, class: &str, date: Option<Date>) {
+        let mut child = self.first_child();
 
-            // row = diff / COLUMNS as i8;
-            // column = diff % COLUMNS as i8;
-        // }
+        while let Some(c) = child {
+            c.remove_css_class(class);
+            child = c.next_sibling();
+        }
 
-        if date > current.last_of_month() {
+        if let Some(child) = date.and_then(|date| self.child_by_date(date)) {
+            child.add_css_class(class);
         }
+    }
+
+    pub fn set_event(&self, event: &Event) {
+        let (from, to) = self.visible_range();
+
+        for (first, last) in event.occurrences(from, to) {
+            for date in first.series(1.day()).take_while(|date| *date <= last) {
+                let Some(child) = self.child_by_date(date) else { continue };
+                let label = child.downcast::<gtk::Label>().unwrap();
 
-        self.child_at(column as _, (row + 1) as _)
+                label.add_css_class("event");
+                label.add_css_class(&event.class);
+
+                if first == last { continue }
+
+                match date {
+                    date if date == first => label.add_css_class("range-start"),
+                    date if date == last  => label.add_css_class("range-end"),
+                    _ => label.add_css_class("range-middle"),
+                }
+            }
+        }
     }
 
-    pub fn set_event(&self, event: crate::event::Event) {
-        // TODO: return result
-        let Some(child) = self.child_by_date(event.start.to_zoned(TimeZone::system())) else { return };
-        let label = child.downcast::<gtk::Label>().unwrap();
+    pub fn set_events(&self, events: &[Event]) {
+        // Events that were moved or deleted on reload must not stay painted. Only the event
+        // classes go, a category can't name one that the grid sets itself.
+        self.remove_classes(&["event", "range-start", "range-middle", "range-end"]);
+        self.remove_categories();
+        self.imp().events.replace(events.to_vec());
 
-        label.add_css_class("event");
-        label.add_css_class(&event.class);
+        for event in events {
+            self.set_event(event);
+        }
+    }
+
+    pub fn set_notes(&self, dates: &[Date]) {
+        self.remove_classes(&["note"]);
+
+        for date in dates {
+            let Some(child) = self.child_by_date(*date) else { continue };
+            child.add_css_class("note");
+        }
+    }
+
+    pub fn set_holidays(&self, holidays: &[Holiday]) {
+        self.remove_classes(&["holiday"]);
+
+        let (from, to) = self.visible_range();
+
+        for (date, _) in holiday::between(holidays, from, to) {
+            let Some(child) = self.child_by_date(date) else { continue };
+            child.add_css_class("holiday");
+        }
+
+        self.imp().holidays.replace(holidays.to_vec());
+    }
+
+    fn remove_classes(&self, classes: &[&str]) {
+        let mut child = self.first_child();
+
+        while let Some(c) = child {
+            for class in classes {
+                c.remove_css_class(class);
+            }
+
+            child = c.next_sibling();
+        }
+    }
+
+    fn remove_categories(&self) {
+        let mut child = self.first_child();
+
+        while let Some(c) = child {
+            for class in c.css_classes().iter().filter(|class| class.starts_with("category-")) {
+                c.remove_css_class(class);
+            }
+
+            child = c.next_sibling();
+        }
+    }
+
+    // Holidays and events of a day, each event with the part of it that falls on the day
+    fn tooltip_markup(&self, date: Date) -> Option<String> {
+        let mut lines: Vec<String> = holiday::between(&self.imp().holidays.borrow(), date, date).iter()
+            .map(|(_, holiday)| format!("<b>{}</b>", glib::markup_escape_text(&holiday.name)))
+            .collect();
+
+        for event in self.imp().events.borrow().iter() {
+            for (start, end) in event.spans(date, date) {
+                let summary = glib::markup_escape_text(&event.summary);
+
+                let line = match event.time_range_on(&start, &end, date) {
+                    Some(time) => format!("{time} {summary}"),
+                    None       => summary.to_string(),
+                };
+
+                lines.push(line);
+
+                if !event.description.is_empty() {
+                    lines.push(format!("<small>{}</small>", glib::markup_escape_text(&event.description)));
+                }
+            }
+        }
+
+        (!lines.is_empty()).then(|| lines.join("\n"))
     }
 
     pub fn set_date(&self, year: u16, month: u8) {
@@ -226,20 +316,6 @@ impl MonthGrid {
         self.fill();
     }
 
-    pub fn from_date(year: u16, month: u8) -> Self {
-        let grid: Self = Object::builder().build();
-        grid.add_css_class("month");
-        grid.set_column_homogeneous(true);
-        grid.set_row_homogeneous(true);
-
-        grid.set_year(year as u32);
-        grid.set_month(month);
-
-        grid.fill();
-
-        grid
-    }
-
     fn clear(&self) {
         while let Some(child) = self.first_child() {
             self.remove(&child);
@@ -249,6 +325,7 @@ impl MonthGrid {
     fn fill(&self) {
         let first: &str = &self.first();
         let max = self.max_weekday_chars() as usize;
+        let offset = self.offset();
 
         for (i, day) in cal::weekdays_with_first(first).iter().enumerate() {
             // `set_max_width_chars` doesn't work, like pretty much everything else in GTK, whatever
@@ -256,47 +333,65 @@ impl MonthGrid {
             let label = gtk::Label::new(Some(&day));
             label.add_css_class("weekday");
 
-            self.attach(&label, i as i32, 0, 1, 1);
+            self.attach(&label, i as i32 + offset, 0, 1, 1);
         }
 
-        let day_for = cal::day_for(self.year() as _, self.month(), first);
         let first: u8 = cal::first_day(first);
-
-        let (mut start, mut end) = (None, None);
+        let page = cal::Page::new(self.year() as _, self.month() as _, first);
+        let system = calendar::system(&self.calendar());
 
         for row in 0..ROWS {
             for column in 0..COLUMNS {
-                let day = day_for(column, row);
+                let day = page.day(column, row);
 
                 let label = gtk::Label::new(None);
                 label.add_css_class("day");
 
                 match day {
                     CalendarDay::Previous(_) => label.add_css_class("previous"),
-                    CalendarDay::Current(_) => {
-                        label.add_css_class("current");
-                        if start.is_none() { start = Some(imp::GridCell { column, row: row + 1 }) }
-                    },
-                    CalendarDay::Next(_) => {
-                        label.add_css_class("next");
-                        if end.is_none() { end = Some(imp::GridCell { column, row: row + 1 }) }
-                    },
+                    CalendarDay::Current(_) => label.add_css_class("current"),
+                    CalendarDay::Next(_) => label.add_css_class("next"),
                 }
 
                 let day: u8 = day.into();
-                label.set_label(&day.to_string());
 
-                if (column + first) % 6 == 0 || (column + first) % 7 == 0 {
+                let secondary = system
+                    .zip(page.date(column, row))
+                    .and_then(|(system, date)| system.date(date));
+
+                match secondary {
+                    Some(date) => {
+                        label.set_markup(&format!("{day}\n<small>{}</small>", date.day));
+                        label.set_justify(gtk::Justification::Center);
+                        label.add_css_class("secondary");
+                    },
+                    None => label.set_label(&day.to_string()),
+                }
+
+                if cal::is_weekend(self.weekend(), column + first) {
                     label.add_css_class("weekend");
                 }
 
-                self.attach(&label, column as i32, row as i32 + 1, 1, 1);
+                self.attach(&label, column as i32 + offset, row as i32 + 1, 1, 1);
             }
         }
 
-        #[allow(clippy::unnecessary_unwrap)]
-        if start.is_some() && end.is_some() {
-            self.set_month_range(imp::Range { start: start.unwrap(), end: end.unwrap() });
+        if self.week_numbers() {
+            self.fill_week_numbers(&page, first);
+        }
+    }
+
+    fn fill_week_numbers(&self, page: &cal::Page, first: u8) {
+        // A row belongs to the ISO week that holds most of its days, which is the week of its Thursday
+        let thursday = (Weekday::Thursday.to_sunday_zero_offset() as i64 - first as i64).rem_euclid(COLUMNS as i64);
+
+        for row in 0..ROWS {
+            let Some(date) = page.date(thursday as u8, row) else { continue };
+
+            let label = gtk::Label::new(Some(&date.iso_week_date().week().to_string()));
+            label.add_css_class("weeknumber");
+
+            self.attach(&label, 0, row as i32 + 1, 1, 1);
         }
     }
 }
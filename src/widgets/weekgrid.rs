@@ -0,0 +1,297 @@
+use gtk::graphene::Point;
+use gtk::glib::{self, Object};
+use gtk::prelude::{GridExt, WidgetExt};
+use gtk::subclass::prelude::ObjectSubclassIsExt;
+
+use jiff::civil::Date;
+use jiff::ToSpan;
+
+use crate::cal;
+use crate::event::Event;
+
+pub const HOURS: i32 = 24;
+pub const COLUMNS: i32 = crate::cal::WEEKDAYS.len() as i32;
+
+// Grid columns of a day, so that overlapping events can share it side by side
+const LANES: i32 = 12;
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use glib::Properties;
+    use glib::subclass::types::ObjectSubclass;
+    use glib::subclass::object::ObjectImpl;
+
+    use gtk::graphene::Point;
+    use gtk::prelude::{ObjectExt, SnapshotExt};
+    use gtk::subclass::grid::GridImpl;
+    use gtk::subclass::widget::{WidgetImpl, WidgetImplExt};
+    use gtk::subclass::orientable::OrientableImpl;
+    use gtk::subclass::prelude::DerivedObjectProperties;
+    use gtk::Snapshot;
+
+    use jiff::civil::Date;
+
+    #[derive(Properties, Default)]
+    #[properties(wrapper_type = super::WeekGrid)]
+    pub struct WeekGrid {
+        #[property(get, set)]
+        first: RefCell<String>,
+
+        #[property(get, set)]
+        max_weekday_chars: Cell<u8>,
+
+        #[property(get, set)]
+        translate: Cell<Point>,
+
+        pub start: Cell<Date>,
+    }
+
+    impl WidgetImpl for WeekGrid {
+        fn snapshot(&self, snapshot: &Snapshot) {
+            snapshot.translate(&self.translate.get());
+            self.parent_snapshot(snapshot);
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for WeekGrid {
+        const NAME: &'static str = "WeekGrid";
+        type Type = super::WeekGrid;
+        type ParentType = gtk::Grid;
+    }
+
+    #[glib::derived_properties]
+    impl ObjectImpl for WeekGrid {}
+    impl OrientableImpl for WeekGrid {}
+    impl GridImpl for WeekGrid {}
+}
+
+glib::wrapper! {
+    pub struct WeekGrid(ObjectSubclass<imp::WeekGrid>)
+        @extends gtk::Grid, gtk::Widget,
+        @implements gtk::Accessible, gtk::Actionable, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl Default for WeekGrid {
+    fn default() -> Self {
+        let grid: Self = Object::builder().build();
+        grid.add_css_class("week");
+        grid.set_column_homogeneous(true);
+        grid.connect_translate_notify(|grid| grid.queue_draw());
+
+        grid
+    }
+}
+
+impl WeekGrid {
+    /// First day of the displayed week.
+    pub fn start(&self) -> Date {
+        self.imp().start.get()
+    }
+
+    /// Shows the week that contains `date`.
+    pub fn set_date(&self, date: Date) {
+        let first = cal::first_day(&self.first()) as i8;
+        let offset = (date.weekday().to_sunday_zero_offset() - first + COLUMNS as i8) % COLUMNS as i8;
+
+        self.imp().start.set(date - (offset as i64).days());
+        self.set_translate(Point::zero());
+        self.clear();
+        self.fill();
+    }
+
+    pub fn set_today(&self, date: Date) {
+        self.mark_single("today", Some(date));
+    }
+
+    pub fn set_focused(&self, date: Option<Date>) {
+        self.mark_single("focused", date);
+    }
+
+    // Moves `class` to the weekday of `date`, removing it from the others
+    fn mark_single(&self, class: &str, date: Option<Date>) {
+        for column in 0..COLUMNS {
+            let Some(label) = self.child_at(Self::column(column), 0) else { continue };
+
+            match date.is_some_and(|date| self.day(date) == Some(column)) {
+                true  => label.add_css_class(class),
+                false => label.remove_css_class(class),
+            }
+        }
+    }
+
+    // Day of the displayed week, counted from its first
+    fn day(&self, date: Date) -> Option<i32> {
+        let day = date.since(self.start()).ok()?.get_days();
+
+        (0..COLUMNS).contains(&day).then_some(day)
+    }
+
+    // First grid column of a day, the hours take the lanes before the first one
+    fn column(day: i32) -> i32 {
+        (day + 1) * LANES
+    }
+
+    pub fn set_events(&self, events: &[Event]) {
+        let start = self.start();
+        let end = start.saturating_add((COLUMNS as i64 - 1).days());
+
+        self.clear_events();
+
+        let mut all_day = Vec::new();
+        let mut timed = vec![Vec::new(); COLUMNS as usize];
+
+        for event in events {
+            for (from, to) in event.spans(start, end) {
+                // DTEND is exclusive, an event until midnight stays within its day
+                if event.is_all_day() || Event::last_day(&from, &to) != from.date() {
+                    let first = self.day(from.date()).unwrap_or(0);
+                    let last = self.day(Event::last_day(&from, &to)).unwrap_or(COLUMNS - 1);
+
+                    all_day.push(((first, last + 1), event));
+                    continue
+                }
+
+                let Some(day) = self.day(from.date()) else { continue };
+
+                // Events that end within an hour still occupy its slot, one that ends at midnight runs to the last row
+                let partial = to.minute() != 0 || to.second() != 0;
+                let last = match to.date() > from.date() {
+                    true  => HOURS,
+                    false => to.hour() as i32 + partial as i32,
+                };
+
+                let first = from.hour() as i32;
+
+                timed[day as usize].push(((first, last.max(first + 1)), event));
+            }
+        }
+
+        // Overlapping all-day events get a row each, the hours start below them
+        let rows = self.attach_all_day(&mut all_day);
+
+        self.attach_hours(1 + rows.max(1));
+
+        for (day, events) in timed.iter_mut().enumerate() {
+            self.attach_timed(day as i32, 1 + rows.max(1), events);
+        }
+    }
+
+    // Returns the number of rows taken
+    fn attach_all_day(&self, events: &mut [((i32, i32), &Event)]) -> i32 {
+        let (lanes, count) = lanes(events);
+
+        for (((first, end), event), lane) in events.iter().zip(lanes) {
+            let label = Self::event_label(event);
+            self.attach(&label, Self::column(*first), 1 + lane, (end - first) * LANES, 1);
+        }
+
+        count
+    }
+
+    fn attach_timed(&self, day: i32, first_row: i32, events: &mut [((i32, i32), &Event)]) {
+        let (lanes, count) = lanes(events);
+        let count = count.min(LANES);
+        let width = |lane: i32| lane * LANES / count;
+
+        for (i, ((first, end), event)) in events.iter().enumerate() {
+            let lane = lanes[i].min(count - 1);
+
+            // Spreads over the free lanes on its right
+            let next = events.iter().zip(&lanes)
+                .filter(|(((f, e), _), other)| **other > lane && f < end && first < e)
+                .map(|(_, other)| (*other).min(count))
+                .min()
+                .unwrap_or(count);
+
+            let label = Self::event_label(event);
+            let column = Self::column(day) + width(lane);
+
+            self.attach(&label, column, first + first_row, Self::column(day) + width(next) - column, end - first);
+        }
+    }
+
+    fn event_label(event: &Event) -> gtk::Label {
+        let label = gtk::Label::new(Some(&event.summary));
+        label.add_css_class("event");
+        label.add_css_class(&event.class);
+        label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        label.set_valign(gtk::Align::Fill);
+
+        let tooltip = match event.description.is_empty() {
+            true  => event.summary.clone(),
+            false => format!("{}\n{}", event.summary, event.description),
+        };
+
+        label.set_tooltip_text(Some(&tooltip));
+
+        label
+    }
+
+    fn clear_events(&self) {
+        let mut child = self.first_child();
+
+        while let Some(c) = child {
+            child = c.next_sibling();
+
+            if c.has_css_class("event") || c.has_css_class("hour") {
+                self.remove(&c);
+            }
+        }
+    }
+
+    fn clear(&self) {
+        while let Some(child) = self.first_child() {
+            self.remove(&child);
+        }
+    }
+
+    fn fill(&self) {
+        let start = self.start();
+        let max = self.max_weekday_chars() as usize;
+
+        for (i, day) in cal::weekdays_with_first(&self.first()).iter().enumerate() {
+            let Ok(date) = start.checked_add((i as i64).days()) else { continue };
+            let day: String = day.chars().take(max).collect();
+
+            let label = gtk::Label::new(Some(&format!("{day} {}", date.day())));
+            label.add_css_class("weekday");
+
+            self.attach(&label, Self::column(i as i32), 0, LANES, 1);
+        }
+
+        self.attach_hours(2);
+    }
+
+    fn attach_hours(&self, first_row: i32) {
+        for hour in 0..HOURS {
+            let label = gtk::Label::new(Some(&format!("{hour:02}")));
+            label.add_css_class("hour");
+            label.set_valign(gtk::Align::Start);
+
+            self.attach(&label, 0, hour + first_row, LANES, 1);
+        }
+    }
+}
+
+// Puts ranges that overlap into different lanes, sorting them by start.
+// Returns the lane of each and the number of lanes.
+fn lanes<T>(ranges: &mut [((i32, i32), T)]) -> (Vec<i32>, i32) {
+    ranges.sort_by_key(|((first, end), _)| (*first, -*end));
+
+    let mut ends: Vec<i32> = Vec::new();
+
+    let lanes = ranges.iter()
+        .map(|((first, end), _)| {
+            let lane = match ends.iter().position(|last| last <= first) {
+                Some(lane) => { ends[lane] = *end; lane },
+                None => { ends.push(*end); ends.len() - 1 },
+            };
+
+            lane as i32
+        })
+        .collect();
+
+    (lanes, ends.len() as i32)
+}
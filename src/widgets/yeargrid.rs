@@ -1,13 +1,18 @@
 use gtk::glib::{self, Object};
-use gtk::prelude::{GridExt, WidgetExt};
+use gtk::prelude::{Cast, GridExt, WidgetExt};
+use gtk::PickFlags;
+
+use jiff::civil::Date;
 
 use super::monthgrid::MonthGrid;
+use crate::event::Event;
+use crate::holiday::Holiday;
 
 pub const ROWS: i32 = 4;
 pub const COLUMNS: i32 = crate::cal::MONTHS.len() as i32 / ROWS;
 
 mod imp {
-    use std::cell::Cell;
+    use std::cell::{Cell, RefCell};
 
     use glib::Properties;
     use glib::subclass::types::ObjectSubclass;
@@ -22,6 +27,12 @@ mod imp {
     #[derive(Properties, Default)]
     #[properties(wrapper_type = super::YearGrid)]
     pub struct YearGrid {
+        #[property(get, set)]
+        first: RefCell<String>,
+
+        #[property(get, set)]
+        weekend: Cell<u8>,
+
         #[property(get, set)]
         active: Cell<f64>,
     }
@@ -47,8 +58,8 @@ glib::wrapper! {
         @implements gtk::Accessible, gtk::Actionable, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
 }
 
-impl YearGrid {
-    pub fn from_date(year: u16) -> Self {
+impl Default for YearGrid {
+    fn default() -> Self {
         let grid: Self = Object::builder().build();
         grid.add_css_class("year");
 
@@ -58,13 +69,73 @@ impl YearGrid {
         grid.set_row_spacing(10);
         grid.set_column_spacing(10);
 
-        for column in 0..COLUMNS {
-            for row in 0..ROWS {
-                let month = MonthGrid::from_date(year, (column * row  + column) as u8 + 1);
-                grid.attach(&month, column, row, 1, 1);
+        grid
+    }
+}
+
+impl YearGrid {
+    pub fn set_date(&self, year: u16) {
+        while let Some(child) = self.first_child() {
+            self.remove(&child);
+        }
+
+        for row in 0..ROWS {
+            for column in 0..COLUMNS {
+                let month = MonthGrid::default();
+                month.set_first(self.first());
+                month.set_weekend(self.weekend());
+                month.set_max_weekday_chars(1);
+                month.set_date(year, (row * COLUMNS + column) as u8 + 1);
+
+                self.attach(&month, column, row, 1, 1);
             }
         }
+    }
 
-        grid
+    pub fn set_events(&self, events: &[Event]) {
+        let mut child = self.first_child();
+
+        while let Some(month) = child {
+            if let Some(month) = month.downcast_ref::<MonthGrid>() {
+                month.set_events(events);
+            }
+
+            child = month.next_sibling();
+        }
+    }
+
+    pub fn set_today(&self, date: Date) {
+        let mut child = self.first_child();
+
+        while let Some(month) = child {
+            if let Some(month) = month.downcast_ref::<MonthGrid>() {
+                month.set_today(date);
+            }
+
+            child = month.next_sibling();
+        }
+    }
+
+    pub fn set_holidays(&self, holidays: &[Holiday]) {
+        let mut child = self.first_child();
+
+        while let Some(month) = child {
+            if let Some(month) = month.downcast_ref::<MonthGrid>() {
+                month.set_holidays(holidays);
+            }
+
+            child = month.next_sibling();
+        }
+    }
+
+    /// Month of the small grid under a point in the grid's coordinates.
+    pub fn month_at(&self, x: f64, y: f64) -> Option<u8> {
+        let mut child = self.pick(x, y, PickFlags::DEFAULT)?;
+
+        while child.parent().as_ref() != Some(self.upcast_ref()) {
+            child = child.parent()?;
+        }
+
+        child.downcast_ref::<MonthGrid>().map(MonthGrid::month)
     }
 }
@@ -19,6 +19,10 @@ pub fn cache_dir() -> PathBuf {
     env_or_default("XDG_CACHE_HOME", ".cache")
 }
 
+pub fn data_dir() -> PathBuf {
+    env_or_default("XDG_DATA_HOME", ".local/share")
+}
+
 enum Platform {
     Wayland,
     X11,